@@ -13,8 +13,10 @@ use uuid::Uuid;
 
 use crate::messaging;
 use crate::structs::{
-    APISearchQuery, APISpot, HorizonEventsCollection, Location, SearchError, SearchQuery,
-    SearchQueryMessage, SearchResponse, SpotAnswerStatus, SpotsSuccess,
+    APIBestDaysQuery, APISearchQuery, APISpot, APISunlightQuery, APITrackQuery, AngleUnit, Angles,
+    AzimuthMatches, HorizonEventsCollection, LightWindows, Location, SearchError, SearchQuery,
+    SearchQueryMessage, SearchResponse, SpotAnswerStatus, SpotQueryMessage, SpotsSuccess, Sunlight,
+    Tracks,
 };
 
 ///////////
@@ -66,6 +68,104 @@ impl Query {
     fn backend_version() -> &'static str {
         BACKEND_VERSION
     }
+
+    /// Rises and sets of the sun and the moon within an azimuth range over a number of days
+    async fn best_days(
+        #[graphql(context)] context: &Context,
+        query: APIBestDaysQuery,
+    ) -> Result<AzimuthMatches, FieldError> {
+        if context.fake {
+            return Ok(AzimuthMatches::default());
+        }
+
+        let unit = query.angle_unit.unwrap_or_default();
+        let response = spot_request(context, SearchQuery::from(query)).await?;
+
+        Ok(response.azimuth_matches.unwrap_or_default().in_unit(unit))
+    }
+
+    /// Sampled paths of the sun and the moon across the sky during one day
+    async fn tracks(
+        #[graphql(context)] context: &Context,
+        query: APITrackQuery,
+    ) -> Result<Tracks, FieldError> {
+        if context.fake {
+            return Ok(Tracks::default());
+        }
+
+        let unit = query.angle_unit.unwrap_or_default();
+        let response = spot_request(context, SearchQuery::from(query)).await?;
+
+        Ok(response.tracks.unwrap_or_default().in_unit(unit))
+    }
+
+    /// Periods of a day during which a spot lies in direct sunlight
+    async fn sunlight(
+        #[graphql(context)] context: &Context,
+        query: APISunlightQuery,
+    ) -> Result<Sunlight, FieldError> {
+        if context.fake {
+            return Ok(Sunlight::default());
+        }
+
+        let unit = query.angle_unit.unwrap_or_default();
+        let response = spot_request(context, SearchQuery::from(query)).await?;
+
+        Ok(response.sunlight.unwrap_or_default().in_unit(unit))
+    }
+}
+
+/// Send a search for the single spot at the searched location and wait for its response
+async fn spot_request(
+    context: &Context,
+    search_query: SearchQuery,
+) -> Result<SearchResponse, FieldError> {
+    let request_id = Uuid::new_v4().to_string();
+    let spot_message = SpotQueryMessage::new(request_id.clone(), search_query);
+
+    let mut messages = messaging::get_messages_stream(&context.jetstream, &request_id)
+        .await
+        .map_err(|err| {
+            error!("Couldn't subscribe to NATS: {err}");
+            FieldError::new(
+                "Couldn't subscribe to NATS",
+                graphql_value!(err.to_string()),
+            )
+        })?;
+
+    messaging::send_spot_query(&context.jetstream, spot_message)
+        .await
+        .map_err(|err| {
+            error!("Couldn't send spot query to NATS");
+            FieldError::new(
+                "Couldn't send spot query to NATS",
+                graphql_value!(err.to_string()),
+            )
+        })?;
+
+    let response = match messages.next().await {
+        None => Err(FieldError::new(
+            "No response received",
+            graphql_value!(request_id.clone()),
+        )),
+        Some(Err(error)) => Err(FieldError::new(
+            "Error while receiving responses",
+            graphql_value!(error.to_string()),
+        )),
+        Some(Ok(message)) => {
+            let response = decode_response(&message);
+            message.ack().await?;
+            response
+        }
+    };
+
+    match messaging::delete_consumer(&request_id).await {
+        Err(error) => warn!("Error occured while deleting consumer: {}", error),
+        Ok(false) => warn!("Could not delete consumer for request {}", request_id),
+        _ => (),
+    }
+
+    response
 }
 
 ///////////////////
@@ -91,7 +191,9 @@ impl Subscription {
 fn fake_result_stream(query: APISearchQuery) -> SpotStreamPin {
     let lat = query.location.lat;
     let lon = query.location.lon;
-    let events = HorizonEventsCollection::fake();
+    let unit = query.angle_unit.unwrap_or_default();
+    let events = HorizonEventsCollection::fake().in_unit(unit);
+    let windows = LightWindows::fake().in_unit(unit);
     let dist = 0.001;
     Box::pin(stream! {
         for i in 0..4 {
@@ -107,6 +209,10 @@ fn fake_result_stream(query: APISearchQuery) -> SpotStreamPin {
                     location: Location { lat, lon },
                     kind: String::from("fake"),
                     events: events.clone(),
+                    windows: Some(windows.clone()),
+                    series: vec![],
+                    horizon: None,
+                    horizon_summary: None,
                 },
             })
         }
@@ -116,6 +222,7 @@ fn fake_result_stream(query: APISearchQuery) -> SpotStreamPin {
 async fn result_stream(context: &Context, search_query: APISearchQuery) -> SpotStreamPin {
     let request_id = Uuid::new_v4().to_string();
 
+    let unit = search_query.angle_unit.unwrap_or_default();
     let search_query = SearchQuery::from(search_query);
     let search_message = SearchQueryMessage {
         request_id: request_id.clone(),
@@ -134,11 +241,15 @@ async fn result_stream(context: &Context, search_query: APISearchQuery) -> SpotS
 
     match sent {
         Err(err_stream) => err_stream,
-        Ok(_) => connect_to_response_messages(context, request_id).await,
+        Ok(_) => connect_to_response_messages(context, request_id, unit).await,
     }
 }
 
-async fn connect_to_response_messages(context: &Context, request_id: String) -> SpotStreamPin {
+async fn connect_to_response_messages(
+    context: &Context,
+    request_id: String,
+    unit: AngleUnit,
+) -> SpotStreamPin {
     let messages = messaging::get_messages_stream(&context.jetstream, &request_id)
         .await
         .map_err(|err| {
@@ -153,13 +264,14 @@ async fn connect_to_response_messages(context: &Context, request_id: String) ->
 
     match messages {
         Err(error_stream) => error_stream,
-        Ok(messages) => translate_response_messages(messages, request_id).await,
+        Ok(messages) => translate_response_messages(messages, request_id, unit).await,
     }
 }
 
 async fn translate_response_messages(
     mut messages: MessageStream,
     request_id: String,
+    unit: AngleUnit,
 ) -> SpotStreamPin {
     Box::pin(stream! {
         let mut received_ids = HashSet::<u32>::new();
@@ -171,7 +283,7 @@ async fn translate_response_messages(
                     graphql_value!(error.to_string()),
                 )),
                 Ok(message) => {
-                    let (spot, last) = transform_spot_message(&message, &mut received_ids)?;
+                    let (spot, last) = transform_spot_message(&message, &mut received_ids, unit)?;
                     yield Ok(spot);
                     message.ack().await?;
                     if last {
@@ -193,41 +305,46 @@ async fn translate_response_messages(
 fn transform_spot_message(
     message: &Message,
     received_ids: &mut HashSet<u32>,
+    unit: AngleUnit,
 ) -> Result<(SpotsSuccess, bool), FieldError> {
+    let response = decode_response(message)?;
+
+    info!(
+        "Received response from microservices:\nrequest_id: {}\nnumber {} of {}",
+        response.request_id, response.part.id, response.part.of
+    );
+
+    // This implementation using a HashSet is wasteful
+    // TODO: Maybe alternative implementation using a vector?
+    if received_ids.is_empty() {
+        for id in 0..response.part.of {
+            received_ids.insert(id);
+        }
+    }
+    let duplicate = received_ids.remove(&response.part.id);
+    if duplicate {
+        // TODO: send out error that duplicate value was received
+    }
+
+    let last = received_ids.is_empty();
+
+    let status = if last {
+        SpotAnswerStatus::Finished
+    } else {
+        SpotAnswerStatus::Running
+    };
+    let spot = APISpot::from(response).in_unit(unit);
+
+    Ok((SpotsSuccess { status, spot }, last))
+}
+
+fn decode_response(message: &Message) -> Result<SearchResponse, FieldError> {
     let payload_str = str::from_utf8(&message.payload)?;
     let res_response: Result<SearchResponse, serde_json::Error> = serde_json::from_str(payload_str);
     let err_response: Result<SearchError, serde_json::Error> = serde_json::from_str(payload_str);
 
     match (res_response, err_response) {
-        (Ok(response), _) => {
-            info!(
-                "Received response from microservices:\nrequest_id: {}\nnumber {} of {}",
-                response.request_id, response.part.id, response.part.of
-            );
-
-            // This implementation using a HashSet is wasteful
-            // TODO: Maybe alternative implementation using a vector?
-            if received_ids.is_empty() {
-                for id in 0..response.part.of {
-                    received_ids.insert(id);
-                }
-            }
-            let duplicate = received_ids.remove(&response.part.id);
-            if duplicate {
-                // TODO: send out error that duplicate value was received
-            }
-
-            let last = received_ids.is_empty();
-
-            let status = if last {
-                SpotAnswerStatus::Finished
-            } else {
-                SpotAnswerStatus::Running
-            };
-            let spot = APISpot::from(response);
-
-            Ok((SpotsSuccess { status, spot }, last))
-        }
+        (Ok(response), _) => Ok(response),
         (_, Ok(err_response)) => {
             error!("Received error from microservices: {:?}", err_response);
 
@@ -3,33 +3,24 @@ use futures_util::stream::select;
 
 use log::info;
 use messages_common::MessageStream;
-use serde::{Deserialize, Serialize};
 use std::{error::Error, str};
 
-use crate::structs::SearchQueryMessage;
+use crate::structs::{SearchQueryMessage, SpotQueryMessage};
 
 const SEARCH_STREAM: &str = "SEARCH";
 const SEARCH_Q: &str = "SEARCH.request";
 
+const SPOT_STREAM: &str = "SPOTS";
+const SPOT_Q: &str = "SPOTS.get-horizon";
+
 const IN_STREAM: &str = "SUNSETS";
 const IN_ERR_STREAM: &str = "ERRORS";
 
 const GROUP: &str = "api";
 
-#[derive(Serialize, Deserialize)]
-struct Location {
-    lat: f64,
-    lon: f64,
-}
-
-#[derive(Serialize, Deserialize)]
-struct SearchQuery {
-    loc: Location,
-    rad: i32,
-}
-
 pub async fn create_streams(jetstream: &Context) {
     messages_common::create_stream(jetstream, SEARCH_STREAM).await;
+    messages_common::create_stream(jetstream, SPOT_STREAM).await;
 }
 
 pub async fn send_search_query(
@@ -46,6 +37,20 @@ pub async fn send_search_query(
     Ok(())
 }
 
+pub async fn send_spot_query(
+    jetstream: &Context,
+    message: SpotQueryMessage,
+) -> Result<(), async_nats::Error> {
+    let payload = serde_json::to_string(&message)?;
+
+    info!("Sending out spot request {payload}");
+    jetstream
+        .publish(SPOT_Q.to_string(), payload.into())
+        .await?;
+
+    Ok(())
+}
+
 pub async fn get_messages_stream(
     jetstream: &Context,
     request_id: &str,
@@ -1,13 +1,13 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveTime, TimeZone, Utc};
 use chrono_tz::Tz;
 use juniper::{GraphQLEnum, GraphQLInputObject, GraphQLObject};
 use serde::{Deserialize, Serialize};
 
 ////////////
-/// NATS ///
+//  NATS  //
 ////////////
 
-/// In
+// In
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Part {
@@ -17,6 +17,7 @@ pub struct Part {
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Spot {
+    /// Direction the spot faces in degrees, as given by the spot finder
     dir: Option<f64>,
     kind: String,
     loc: Location,
@@ -29,7 +30,18 @@ pub struct SearchResponse {
     search_query: SearchQuery,
     spot: Spot,
     horizon: String,
+    #[serde(default)]
+    horizon_metadata: Option<HorizonMetadata>,
+    #[serde(default)]
+    horizon_summary: Option<HorizonSummary>,
     events: HorizonEventsCollection,
+    #[serde(default)]
+    windows: Option<LightWindows>,
+    #[serde(default)]
+    series: Vec<DayEvents>,
+    pub azimuth_matches: Option<AzimuthMatches>,
+    pub tracks: Option<Tracks>,
+    pub sunlight: Option<Sunlight>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -41,11 +53,53 @@ pub struct SearchError {
 }
 
 ///////////////
-/// GraphQL ///
+//  GraphQL  //
 ///////////////
 
 // Out
 
+/// Unit of the angles in a query and its response
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, GraphQLEnum)]
+pub enum AngleUnit {
+    #[default]
+    Radians,
+    Degrees,
+}
+
+impl AngleUnit {
+    /// The angle given in this unit in radians
+    pub fn to_radians(self, angle: f64) -> f64 {
+        match self {
+            AngleUnit::Radians => angle,
+            AngleUnit::Degrees => angle.to_radians(),
+        }
+    }
+}
+
+/// Responses containing angles, which the services send in radians
+pub trait Angles: Sized {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self;
+
+    fn in_unit(self, unit: AngleUnit) -> Self {
+        match unit {
+            AngleUnit::Radians => self,
+            AngleUnit::Degrees => self.map_angles(f64::to_degrees),
+        }
+    }
+}
+
+impl<T: Angles> Angles for Option<T> {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        self.map(|value| value.map_angles(f))
+    }
+}
+
+impl<T: Angles> Angles for Vec<T> {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        self.into_iter().map(|value| value.map_angles(f)).collect()
+    }
+}
+
 #[derive(Debug, GraphQLObject, Serialize, Deserialize)]
 pub struct Location {
     pub lat: f64,
@@ -65,37 +119,577 @@ impl From<LocationIn> for Location {
 pub struct HorizonEvent {
     pub time: DateTime<Utc>,
     pub altitude: f64,
+    /// Measured from north towards east
     pub azimuth: f64,
 }
 
+impl Angles for HorizonEvent {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            altitude: f(self.altitude),
+            azimuth: f(self.azimuth),
+            ..self
+        }
+    }
+}
+
 #[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
 pub struct HorizonEvents {
     pub rise: HorizonEvent,
     pub set: HorizonEvent,
+    /// The highest point of the object, e.g. the solar noon
+    pub transit: HorizonEvent,
+    /// Time in seconds the object spends above the horizon during the day
+    pub duration: i32,
+}
+
+impl Angles for HorizonEvents {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            rise: self.rise.map_angles(f),
+            set: self.set.map_angles(f),
+            transit: self.transit.map_angles(f),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, GraphQLEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HorizonEventsState {
+    /// Rise and set were found
+    Events,
+    /// The object does not go below the horizon
+    AlwaysUp,
+    /// The object does not rise above the horizon
+    AlwaysDown,
+    /// The object crosses the horizon, but no matching rise and set could be found
+    SolverFailed,
+}
+
+/// Outcome of searching rise and set, `events` is only present for the state `EVENTS`
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct HorizonEventsResult {
+    pub state: HorizonEventsState,
+    pub events: Option<HorizonEvents>,
+}
+
+impl HorizonEventsResult {
+    fn state(state: HorizonEventsState) -> Self {
+        Self {
+            state,
+            events: None,
+        }
+    }
+}
+
+impl Angles for HorizonEventsResult {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            events: self.events.map_angles(f),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, GraphQLEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoonPhaseName {
+    NewMoon,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    FullMoon,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct MoonPhase {
+    pub phase: MoonPhaseName,
+    /// Illuminated fraction of the disc, between 0 and 1
+    pub illuminated_fraction: f64,
+    /// Days since the last new moon
+    pub age: f64,
+    /// Position angle of the bright limb, measured from north towards east
+    pub position_angle: f64,
+}
+
+impl Angles for MoonPhase {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            position_angle: f(self.position_angle),
+            ..self
+        }
+    }
+}
+
+/// Moon phase at the times of moonrise and moonset
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct MoonPhaseEvents {
+    pub rise: MoonPhase,
+    pub set: MoonPhase,
+}
+
+impl Angles for MoonPhaseEvents {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            rise: self.rise.map_angles(f),
+            set: self.set.map_angles(f),
+        }
+    }
+}
+
+/// Twilight boundaries, `rise` is the morning and `set` the evening event
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct TwilightEvents {
+    pub civil: HorizonEventsResult,
+    pub nautical: HorizonEventsResult,
+    pub astronomical: HorizonEventsResult,
+}
+
+impl Angles for TwilightEvents {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            civil: self.civil.map_angles(f),
+            nautical: self.nautical.map_angles(f),
+            astronomical: self.astronomical.map_angles(f),
+        }
+    }
+}
+
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct PlanetEvents {
+    pub mercury: HorizonEventsResult,
+    pub venus: HorizonEventsResult,
+    pub mars: HorizonEventsResult,
+    pub jupiter: HorizonEventsResult,
+    pub saturn: HorizonEventsResult,
+}
+
+impl Angles for PlanetEvents {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            mercury: self.mercury.map_angles(f),
+            venus: self.venus.map_angles(f),
+            mars: self.mars.map_angles(f),
+            jupiter: self.jupiter.map_angles(f),
+            saturn: self.saturn.map_angles(f),
+        }
+    }
+}
+
+/// Horizon events of a named star or deep-sky object
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct CatalogObjectEvents {
+    pub name: String,
+    /// Unknown if the name is not in the catalog
+    pub events: Option<HorizonEventsResult>,
+}
+
+impl Angles for CatalogObjectEvents {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            events: self.events.map_angles(f),
+            ..self
+        }
+    }
+}
+
+/// A period during which the object is above the horizon
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct VisibilityInterval {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Missing if the object is already up at the start of the day
+    pub rise: Option<HorizonEvent>,
+    /// Missing if the object is still up at the end of the day
+    pub set: Option<HorizonEvent>,
+}
+
+impl Angles for VisibilityInterval {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            rise: self.rise.map_angles(f),
+            set: self.set.map_angles(f),
+            ..self
+        }
+    }
+}
+
+/// Periods of the day during which a spot lies in direct sunlight
+#[derive(Debug, Clone, Default, GraphQLObject, Serialize, Deserialize)]
+pub struct Sunlight {
+    pub intervals: Vec<VisibilityInterval>,
+    /// Total time in sunlight in minutes
+    pub minutes: i32,
+}
+
+impl Angles for Sunlight {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            intervals: self.intervals.map_angles(f),
+            ..self
+        }
+    }
+}
+
+/// All periods of the day during which the sun and the moon are above the horizon,
+/// there can be several behind a ragged horizon
+#[derive(Debug, Clone, Default, GraphQLObject, Serialize, Deserialize)]
+pub struct VisibilityIntervals {
+    pub sun: Vec<VisibilityInterval>,
+    pub moon: Vec<VisibilityInterval>,
+}
+
+impl Angles for VisibilityIntervals {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            sun: self.sun.map_angles(f),
+            moon: self.moon.map_angles(f),
+        }
+    }
 }
 
 #[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
 pub struct HorizonEventsCollection {
-    sun: Option<HorizonEvents>,
-    moon: Option<HorizonEvents>,
+    sun: HorizonEventsResult,
+    moon: HorizonEventsResult,
+    moon_phase: Option<MoonPhaseEvents>,
+    /// Only sent if the search asks for the twilight
+    #[serde(default)]
+    twilight: Option<TwilightEvents>,
+    /// Only sent if the search asks for the planets
+    #[serde(default)]
+    planets: Option<PlanetEvents>,
+    #[serde(default)]
+    objects: Vec<CatalogObjectEvents>,
+    /// Only sent if the search asks for the intervals
+    #[serde(default)]
+    intervals: Option<VisibilityIntervals>,
+}
+
+impl Angles for HorizonEventsCollection {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            sun: self.sun.map_angles(f),
+            moon: self.moon.map_angles(f),
+            moon_phase: self.moon_phase.map_angles(f),
+            twilight: self.twilight.map_angles(f),
+            planets: self.planets.map_angles(f),
+            objects: self.objects.map_angles(f),
+            intervals: self.intervals.map_angles(f),
+        }
+    }
 }
 
 impl HorizonEventsCollection {
     pub fn fake() -> Self {
         Self {
-            sun: Some(HorizonEvents {
-                rise: HorizonEvent {
-                    time: Utc::now(),
-                    altitude: 0.,
-                    azimuth: 0.,
-                },
-                set: HorizonEvent {
-                    time: Utc::now(),
-                    altitude: 0.,
-                    azimuth: 0.,
-                },
+            sun: HorizonEventsResult {
+                state: HorizonEventsState::Events,
+                events: Some(HorizonEvents {
+                    rise: HorizonEvent {
+                        time: Utc::now(),
+                        altitude: 0.,
+                        azimuth: 0.,
+                    },
+                    set: HorizonEvent {
+                        time: Utc::now(),
+                        altitude: 0.,
+                        azimuth: 0.,
+                    },
+                    transit: HorizonEvent {
+                        time: Utc::now(),
+                        altitude: 0.,
+                        azimuth: 0.,
+                    },
+                    duration: 0,
+                }),
+            },
+            moon: HorizonEventsResult::state(HorizonEventsState::AlwaysDown),
+            moon_phase: None,
+            twilight: Some(TwilightEvents {
+                civil: HorizonEventsResult::state(HorizonEventsState::AlwaysDown),
+                nautical: HorizonEventsResult::state(HorizonEventsState::AlwaysDown),
+                astronomical: HorizonEventsResult::state(HorizonEventsState::AlwaysDown),
+            }),
+            planets: Some(PlanetEvents {
+                mercury: HorizonEventsResult::state(HorizonEventsState::AlwaysDown),
+                venus: HorizonEventsResult::state(HorizonEventsState::AlwaysDown),
+                mars: HorizonEventsResult::state(HorizonEventsState::AlwaysDown),
+                jupiter: HorizonEventsResult::state(HorizonEventsState::AlwaysDown),
+                saturn: HorizonEventsResult::state(HorizonEventsState::AlwaysDown),
             }),
-            moon: None,
+            objects: vec![],
+            intervals: Some(VisibilityIntervals::default()),
+        }
+    }
+}
+
+/// Sun and moon events of a single day in a series
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct DayEvents {
+    /// The date in the timezone of the query
+    pub date: NaiveDate,
+    pub sun: HorizonEventsResult,
+    pub moon: HorizonEventsResult,
+}
+
+impl Angles for DayEvents {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            sun: self.sun.map_angles(f),
+            moon: self.moon.map_angles(f),
+            ..self
+        }
+    }
+}
+
+/// A sampled position of an object on its path across the sky
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct TrackPoint {
+    pub time: DateTime<Utc>,
+    pub altitude: f64,
+    pub azimuth: f64,
+    /// Whether the object is above the horizon, i.e. not hidden by the terrain
+    pub visible: bool,
+}
+
+impl Angles for TrackPoint {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            altitude: f(self.altitude),
+            azimuth: f(self.azimuth),
+            ..self
+        }
+    }
+}
+
+/// Paths of the sun and the moon above the sea-level horizon during one day from the time
+/// of the query
+#[derive(Debug, Clone, Default, GraphQLObject, Serialize, Deserialize)]
+pub struct Tracks {
+    pub sun: Vec<TrackPoint>,
+    pub moon: Vec<TrackPoint>,
+}
+
+impl Angles for Tracks {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            sun: self.sun.map_angles(f),
+            moon: self.moon.map_angles(f),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, GraphQLEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Rise,
+    Set,
+}
+
+/// A rise or set within the requested azimuth range
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct AzimuthMatch {
+    pub kind: EventKind,
+    pub event: HorizonEvent,
+}
+
+impl Angles for AzimuthMatch {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            event: self.event.map_angles(f),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, GraphQLObject, Serialize, Deserialize)]
+pub struct AzimuthMatches {
+    pub sun: Vec<AzimuthMatch>,
+    pub moon: Vec<AzimuthMatch>,
+}
+
+impl Angles for AzimuthMatches {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            sun: self.sun.map_angles(f),
+            moon: self.moon.map_angles(f),
+        }
+    }
+}
+
+/// Time window between two horizon events
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct HorizonWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub azimuth_start: f64,
+    pub azimuth_end: f64,
+}
+
+impl Angles for HorizonWindow {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            azimuth_start: f(self.azimuth_start),
+            azimuth_end: f(self.azimuth_end),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct HorizonWindows {
+    /// `ALWAYS_UP` if the sun does not go below the window, `ALWAYS_DOWN` if it does not
+    /// rise above it. The windows are only found in the state `EVENTS`.
+    pub state: HorizonEventsState,
+    pub morning: Option<HorizonWindow>,
+    pub evening: Option<HorizonWindow>,
+}
+
+impl Angles for HorizonWindows {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            morning: self.morning.map_angles(f),
+            evening: self.evening.map_angles(f),
+            ..self
+        }
+    }
+}
+
+/// Golden and blue hour, relative to the horizon of the spot
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct LightWindows {
+    pub golden_hour: HorizonWindows,
+    pub blue_hour: HorizonWindows,
+}
+
+impl Angles for LightWindows {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            golden_hour: self.golden_hour.map_angles(f),
+            blue_hour: self.blue_hour.map_angles(f),
+        }
+    }
+}
+
+impl LightWindows {
+    pub fn fake() -> Self {
+        let now = Utc::now();
+        let window = HorizonWindow {
+            start: now,
+            end: now + chrono::Duration::try_minutes(23).expect("constant value"),
+            azimuth_start: 0.,
+            azimuth_end: 0.,
+        };
+
+        Self {
+            golden_hour: HorizonWindows {
+                state: HorizonEventsState::Events,
+                morning: None,
+                evening: Some(window),
+            },
+            blue_hour: HorizonWindows {
+                state: HorizonEventsState::AlwaysDown,
+                morning: None,
+                evening: None,
+            },
+        }
+    }
+}
+
+/// Where the horizon of a spot was computed for and where its terrain came from
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct HorizonMetadata {
+    /// Latitude of the observer
+    pub lat: f64,
+    /// Longitude of the observer
+    pub lon: f64,
+    /// Elevation of the observer above sea level (in metres)
+    pub elevation: f64,
+    /// Elevation model the terrain was taken from
+    pub source: String,
+    pub created: DateTime<Utc>,
+}
+
+/// A range of azimuths, from `start` clockwise to `end`. It contains north if `end` is
+/// less than `start`.
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct AzimuthRange {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl Angles for AzimuthRange {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            start: f(self.start),
+            end: f(self.end),
+        }
+    }
+}
+
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct HorizonPoint {
+    /// Measured from north towards east
+    pub azimuth: f64,
+    pub altitude: f64,
+}
+
+impl Angles for HorizonPoint {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            azimuth: f(self.azimuth),
+            altitude: f(self.altitude),
+        }
+    }
+}
+
+/// A local minimum of the horizon, e.g. a gap between two mountains
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct Notch {
+    /// Measured from north towards east
+    pub azimuth: f64,
+    pub altitude: f64,
+    /// How far the horizon rises on either side before it gets lower than the notch, the
+    /// lower of the two
+    pub prominence: f64,
+}
+
+impl Angles for Notch {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            azimuth: f(self.azimuth),
+            altitude: f(self.altitude),
+            prominence: f(self.prominence),
+        }
+    }
+}
+
+/// Features of the terrain around a spot
+#[derive(Debug, Clone, GraphQLObject, Serialize, Deserialize)]
+pub struct HorizonSummary {
+    pub lowest: HorizonPoint,
+    pub highest: HorizonPoint,
+    /// Notches at least 1° deep, by azimuth
+    pub notches: Vec<Notch>,
+    /// Share of the sky hemisphere hidden by the terrain, between 0 and 1
+    pub obstruction: f64,
+    /// Azimuth ranges in which the horizon is lower than 1°, e.g. for an open view to the west
+    pub open_ranges: Vec<AzimuthRange>,
+}
+
+impl Angles for HorizonSummary {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            lowest: self.lowest.map_angles(f),
+            highest: self.highest.map_angles(f),
+            notches: self.notches.map_angles(f),
+            open_ranges: self.open_ranges.map_angles(f),
+            ..self
         }
     }
 }
@@ -105,6 +699,26 @@ pub struct APISpot {
     pub location: Location,
     pub kind: String,
     pub events: HorizonEventsCollection,
+    /// Only sent if the search asks for the light windows
+    pub windows: Option<LightWindows>,
+    /// Events for each day, if a number of days was requested
+    pub series: Vec<DayEvents>,
+    /// Origin of the horizon, unknown for horizons stored without metadata
+    pub horizon: Option<HorizonMetadata>,
+    /// Features of the terrain around the spot, only sent if the search asks for the summary
+    pub horizon_summary: Option<HorizonSummary>,
+}
+
+impl Angles for APISpot {
+    fn map_angles(self, f: fn(f64) -> f64) -> Self {
+        Self {
+            events: self.events.map_angles(f),
+            windows: self.windows.map_angles(f),
+            series: self.series.map_angles(f),
+            horizon_summary: self.horizon_summary.map_angles(f),
+            ..self
+        }
+    }
 }
 
 impl From<SearchResponse> for APISpot {
@@ -113,6 +727,10 @@ impl From<SearchResponse> for APISpot {
             location: value.spot.loc,
             kind: value.spot.kind,
             events: value.events,
+            windows: value.windows,
+            series: value.series,
+            horizon: value.horizon_metadata,
+            horizon_summary: value.horizon_summary,
         }
     }
 }
@@ -130,7 +748,7 @@ pub struct SpotsSuccess {
 }
 
 //////////////////////////////////////////////////////////////////////////////
-/// Search
+// Search
 
 // In
 
@@ -140,19 +758,127 @@ pub struct LocationIn {
     pub lon: f64,
 }
 
+/// Model used for the position of the sun
+#[derive(Debug, Clone, Copy, GraphQLEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SolarModel {
+    Simple,
+    Vsop87,
+}
+
+/// Model used for the position of the moon
+#[derive(Debug, Clone, Copy, GraphQLEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LunarModel {
+    Simple,
+    Meeus,
+}
+
+/// Point of the disc of the sun or the moon defining its rise and set
+#[derive(Debug, Clone, Copy, GraphQLEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Contact {
+    Centre,
+    /// First and last light
+    UpperLimb,
+    LowerLimb,
+}
+
 #[derive(GraphQLInputObject)]
 pub struct APISearchQuery {
     pub time: DateTime<Utc>,
     pub timezone: Tz,
     pub location: LocationIn,
     pub radius: i32,
+    /// Names of stars and deep-sky objects, e.g. "Pleiades" or "Milky Way Core"
+    pub objects: Option<Vec<String>>,
+    /// Defaults to the model configured for the sky service
+    pub solar_model: Option<SolarModel>,
+    /// Defaults to the model configured for the sky service
+    pub lunar_model: Option<LunarModel>,
+    /// Defaults to the centre of the disc
+    pub contact: Option<Contact>,
+    /// Number of consecutive days for which sun and moon events are returned, at most 366
+    pub days: Option<i32>,
+    /// Atmospheric pressure in hPa, estimated from the elevation of each spot if not given
+    pub pressure: Option<f64>,
+    /// Air temperature in °C, estimated from the elevation of each spot if not given
+    pub temperature: Option<f64>,
+    /// Whether the horizon summary of each spot is calculated, defaults to false
+    pub horizon_summary: Option<bool>,
+    /// Whether the rises and sets of the planets are calculated, defaults to false
+    pub planets: Option<bool>,
+    /// Whether the twilight events are calculated, defaults to false
+    pub twilight: Option<bool>,
+    /// Whether the golden and blue hour are calculated, defaults to false
+    pub light_windows: Option<bool>,
+    /// Whether all visibility intervals of the sun and the moon are calculated, defaults to false
+    pub intervals: Option<bool>,
+    /// Unit of the angles in the response, defaults to radians
+    pub angle_unit: Option<AngleUnit>,
+}
+
+/// Search for the days on which the sun or the moon rises or sets within an azimuth range
+/// as seen from a single spot
+#[derive(GraphQLInputObject)]
+pub struct APIBestDaysQuery {
+    /// Start of the search
+    pub time: DateTime<Utc>,
+    pub timezone: Tz,
+    pub location: LocationIn,
+    /// Start of the azimuth range, measured from north towards east
+    pub azimuth_start: f64,
+    /// End of the azimuth range, the range contains north if it is less than the start
+    pub azimuth_end: f64,
+    /// Unit of the azimuth range and of the angles in the response, defaults to radians
+    pub angle_unit: Option<AngleUnit>,
+    /// Number of consecutive days to search, at most 366
+    pub days: i32,
+    /// Defaults to the model configured for the sky service
+    pub solar_model: Option<SolarModel>,
+    /// Defaults to the model configured for the sky service
+    pub lunar_model: Option<LunarModel>,
+    /// Defaults to the centre of the disc
+    pub contact: Option<Contact>,
+}
+
+/// Paths of the sun and the moon across the sky as seen from a single spot
+#[derive(GraphQLInputObject)]
+pub struct APITrackQuery {
+    /// The paths cover one day from this time on
+    pub time: DateTime<Utc>,
+    pub timezone: Tz,
+    pub location: LocationIn,
+    /// Interval in minutes between two points of a path
+    pub step_minutes: i32,
+    /// Defaults to the model configured for the sky service
+    pub solar_model: Option<SolarModel>,
+    /// Defaults to the model configured for the sky service
+    pub lunar_model: Option<LunarModel>,
+    /// Unit of the angles in the response, defaults to radians
+    pub angle_unit: Option<AngleUnit>,
+}
+
+/// Sunlit periods of a single spot during a day
+#[derive(GraphQLInputObject)]
+pub struct APISunlightQuery {
+    pub date: NaiveDate,
+    /// Timezone in which the day is taken
+    pub timezone: Tz,
+    pub location: LocationIn,
+    /// Defaults to the model configured for the sky service
+    pub solar_model: Option<SolarModel>,
+    /// Point of the sun which has to be visible, defaults to the centre of the disc
+    pub contact: Option<Contact>,
+    /// Unit of the angles in the response, defaults to radians
+    pub angle_unit: Option<AngleUnit>,
 }
 
 ////////////
-/// NATS ///
+//  NATS  //
 ////////////
 
-/// Out
+// Out
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SearchQuery {
@@ -160,6 +886,28 @@ pub struct SearchQuery {
     timezone: Tz,
     loc: Location,
     rad: i32,
+    #[serde(default)]
+    objects: Vec<String>,
+    solar_model: Option<SolarModel>,
+    lunar_model: Option<LunarModel>,
+    contact: Option<Contact>,
+    days: Option<u32>,
+    azimuth_search: Option<AzimuthSearch>,
+    track_step: Option<u32>,
+    sunlight_date: Option<NaiveDate>,
+    horizon_summary: bool,
+    planets: bool,
+    twilight: bool,
+    light_windows: bool,
+    intervals: bool,
+    pressure: Option<f64>,
+    temperature: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct AzimuthSearch {
+    range: AzimuthRange,
+    days: u32,
 }
 
 impl From<APISearchQuery> for SearchQuery {
@@ -169,6 +917,117 @@ impl From<APISearchQuery> for SearchQuery {
             timezone: value.timezone,
             loc: value.location.into(),
             rad: value.radius,
+            objects: value.objects.unwrap_or_default(),
+            solar_model: value.solar_model,
+            lunar_model: value.lunar_model,
+            contact: value.contact,
+            days: value.days.map(|days| days.max(0) as u32),
+            azimuth_search: None,
+            track_step: None,
+            sunlight_date: None,
+            horizon_summary: value.horizon_summary.unwrap_or_default(),
+            planets: value.planets.unwrap_or_default(),
+            twilight: value.twilight.unwrap_or_default(),
+            light_windows: value.light_windows.unwrap_or_default(),
+            intervals: value.intervals.unwrap_or_default(),
+            pressure: value.pressure,
+            temperature: value.temperature,
+        }
+    }
+}
+
+impl From<APIBestDaysQuery> for SearchQuery {
+    fn from(value: APIBestDaysQuery) -> Self {
+        let unit = value.angle_unit.unwrap_or_default();
+
+        SearchQuery {
+            time: value.time,
+            timezone: value.timezone,
+            loc: value.location.into(),
+            rad: 0,
+            objects: vec![],
+            solar_model: value.solar_model,
+            lunar_model: value.lunar_model,
+            contact: value.contact,
+            days: None,
+            azimuth_search: Some(AzimuthSearch {
+                range: AzimuthRange {
+                    start: unit.to_radians(value.azimuth_start),
+                    end: unit.to_radians(value.azimuth_end),
+                },
+                days: value.days.max(0) as u32,
+            }),
+            track_step: None,
+            sunlight_date: None,
+            horizon_summary: false,
+            planets: false,
+            twilight: false,
+            light_windows: false,
+            intervals: false,
+            pressure: None,
+            temperature: None,
+        }
+    }
+}
+
+impl From<APITrackQuery> for SearchQuery {
+    fn from(value: APITrackQuery) -> Self {
+        SearchQuery {
+            time: value.time,
+            timezone: value.timezone,
+            loc: value.location.into(),
+            rad: 0,
+            objects: vec![],
+            solar_model: value.solar_model,
+            lunar_model: value.lunar_model,
+            contact: None,
+            days: None,
+            azimuth_search: None,
+            track_step: Some(value.step_minutes.max(0) as u32),
+            sunlight_date: None,
+            horizon_summary: false,
+            planets: false,
+            twilight: false,
+            light_windows: false,
+            intervals: false,
+            pressure: None,
+            temperature: None,
+        }
+    }
+}
+
+impl From<APISunlightQuery> for SearchQuery {
+    fn from(value: APISunlightQuery) -> Self {
+        // Noon is never skipped by a change of the clocks
+        let noon = value
+            .date
+            .and_time(NaiveTime::from_hms_opt(12, 0, 0).expect("valid time"));
+        let time = value
+            .timezone
+            .from_local_datetime(&noon)
+            .earliest()
+            .map_or(noon.and_utc(), |time| time.to_utc());
+
+        SearchQuery {
+            time,
+            timezone: value.timezone,
+            loc: value.location.into(),
+            rad: 0,
+            objects: vec![],
+            solar_model: value.solar_model,
+            lunar_model: None,
+            contact: value.contact,
+            days: None,
+            azimuth_search: None,
+            track_step: None,
+            sunlight_date: Some(value.date),
+            horizon_summary: false,
+            planets: false,
+            twilight: false,
+            light_windows: false,
+            intervals: false,
+            pressure: None,
+            temperature: None,
         }
     }
 }
@@ -178,3 +1037,32 @@ pub struct SearchQueryMessage {
     pub request_id: String,
     pub search_query: SearchQuery,
 }
+
+/// Request for a single spot at the searched location, skipping the spot finder
+#[derive(Serialize, Deserialize)]
+pub struct SpotQueryMessage {
+    request_id: String,
+    search_query: SearchQuery,
+    spot: Spot,
+    part: Part,
+}
+
+impl SpotQueryMessage {
+    pub fn new(request_id: String, search_query: SearchQuery) -> Self {
+        let spot = Spot {
+            dir: None,
+            kind: String::from("custom"),
+            loc: Location {
+                lat: search_query.loc.lat,
+                lon: search_query.loc.lon,
+            },
+        };
+
+        SpotQueryMessage {
+            request_id,
+            search_query,
+            spot,
+            part: Part { id: 0, of: 1 },
+        }
+    }
+}
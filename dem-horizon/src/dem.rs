@@ -0,0 +1,223 @@
+use std::path::Path;
+
+use anyhow::{anyhow, ensure, Error};
+
+use crate::{geotiff, hgt};
+
+/// Points this far outside of a grid (in samples) are still on its edge, to make up for
+/// rounding
+const EDGE_TOLERANCE: f64 = 1e-9;
+
+/// Elevations of the terrain at points on the earth
+pub trait ElevationModel {
+    /// Elevation above sea level (in metres) at the point, `None` outside of the model or
+    /// where it has no data
+    fn elevation(&self, lat: f64, lon: f64) -> Option<f64>;
+
+    /// Name of the dataset the elevations come from
+    fn source(&self) -> String;
+}
+
+/// Elevations sampled on a regular grid of latitudes and longitudes, with the rows going from
+/// north to south and the columns from west to east
+#[derive(Debug, Clone)]
+pub struct Grid {
+    /// Latitude of the first row
+    north: f64,
+    /// Longitude of the first column
+    west: f64,
+    /// Distance between two rows (in degrees)
+    lat_step: f64,
+    /// Distance between two columns (in degrees)
+    lon_step: f64,
+    columns: usize,
+    /// Elevations row by row (in metres), NaN where the model has no data
+    elevations: Vec<f64>,
+    source: String,
+}
+
+impl Grid {
+    pub fn new(
+        north: f64,
+        west: f64,
+        lat_step: f64,
+        lon_step: f64,
+        columns: usize,
+        elevations: Vec<f64>,
+        source: impl Into<String>,
+    ) -> Result<Self, Error> {
+        ensure!(
+            lat_step > 0. && lon_step > 0.,
+            "The spacing of a grid has to be positive"
+        );
+        ensure!(
+            columns >= 2
+                && elevations.len().is_multiple_of(columns)
+                && elevations.len() / columns >= 2,
+            "Expected at least two full rows of {columns} columns, had {} elevations",
+            elevations.len()
+        );
+
+        Ok(Self {
+            north,
+            west,
+            lat_step,
+            lon_step,
+            columns,
+            elevations,
+            source: source.into(),
+        })
+    }
+
+    fn rows(&self) -> usize {
+        self.elevations.len() / self.columns
+    }
+
+    fn sample(&self, row: usize, column: usize) -> f64 {
+        self.elevations[row * self.columns + column]
+    }
+}
+
+impl ElevationModel for Grid {
+    /// The elevation interpolated bilinearly between the four surrounding samples
+    fn elevation(&self, lat: f64, lon: f64) -> Option<f64> {
+        let (last_row, last_column) = (self.rows() - 1, self.columns - 1);
+        let position = |offset: f64, step: f64, last: usize| {
+            let position = offset / step;
+            let range = -EDGE_TOLERANCE..=last as f64 + EDGE_TOLERANCE;
+            range
+                .contains(&position)
+                .then(|| position.clamp(0., last as f64))
+        };
+        let row = position(self.north - lat, self.lat_step, last_row)?;
+        let column = position(lon - self.west, self.lon_step, last_column)?;
+
+        let top = (row.floor() as usize).min(last_row - 1);
+        let left = (column.floor() as usize).min(last_column - 1);
+        let (down, right) = (row - top as f64, column - left as f64);
+
+        let corners = [
+            (top, left, (1. - down) * (1. - right)),
+            (top, left + 1, (1. - down) * right),
+            (top + 1, left, down * (1. - right)),
+            (top + 1, left + 1, down * right),
+        ];
+        let elevation: f64 = corners
+            .into_iter()
+            .filter(|(_, _, weight)| *weight > 0.)
+            .map(|(row, column, weight)| self.sample(row, column) * weight)
+            .sum();
+
+        // A missing sample with any weight leaves the elevation NaN
+        elevation.is_finite().then_some(elevation)
+    }
+
+    fn source(&self) -> String {
+        self.source.clone()
+    }
+}
+
+/// Several grids like neighbouring SRTM tiles, the first one with data at a point is used
+#[derive(Debug, Clone, Default)]
+pub struct Tiles {
+    grids: Vec<Grid>,
+}
+
+impl Tiles {
+    pub fn new(grids: Vec<Grid>) -> Self {
+        Self { grids }
+    }
+
+    /// Read the tiles from SRTM `.hgt` files and GeoTIFFs
+    pub fn open<P: AsRef<Path>>(paths: &[P]) -> Result<Self, Error> {
+        let grids = paths
+            .iter()
+            .map(|path| open(path.as_ref()))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::new(grids))
+    }
+}
+
+impl ElevationModel for Tiles {
+    fn elevation(&self, lat: f64, lon: f64) -> Option<f64> {
+        self.grids.iter().find_map(|grid| grid.elevation(lat, lon))
+    }
+
+    fn source(&self) -> String {
+        let mut sources: Vec<String> = self.grids.iter().map(Grid::source).collect();
+        sources.dedup();
+        sources.join(", ")
+    }
+}
+
+/// Read an SRTM `.hgt` tile or a GeoTIFF, depending on the extension of the file
+pub fn open(path: &Path) -> Result<Grid, Error> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+
+    match extension.as_deref() {
+        Some("hgt") => hgt::read_hgt(path),
+        Some("tif" | "tiff") => geotiff::read_geotiff(path),
+        _ => Err(anyhow!(
+            "Unknown elevation model '{}', expected an .hgt or .tif file",
+            path.display()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(is: Option<f64>, want: f64) {
+        let is = is.unwrap();
+        assert!((is - want).abs() < 1e-9, "{is} != {want}");
+    }
+
+    #[test]
+    fn interpolate_bilinear() {
+        // 2 rows and 3 columns, 0.5° apart
+        let grid = Grid::new(
+            48.,
+            11.,
+            0.5,
+            0.5,
+            3,
+            vec![100., 200., 300., 300., 400., f64::NAN],
+            "test",
+        )
+        .unwrap();
+
+        assert_close(grid.elevation(48., 11.), 100.);
+        assert_close(grid.elevation(47.5, 11.5), 400.);
+        assert_close(grid.elevation(47.75, 11.25), 250.);
+        assert_close(grid.elevation(48., 11.75), 250.);
+
+        // A missing sample spoils its surroundings
+        assert_eq!(grid.elevation(47.75, 11.75), None);
+        assert_close(grid.elevation(48., 12.), 300.);
+
+        assert_eq!(grid.elevation(48.1, 11.5), None);
+        assert_eq!(grid.elevation(47.75, 10.9), None);
+
+        assert!(Grid::new(48., 11., 0.5, 0.5, 3, vec![0.; 5], "test").is_err());
+        assert!(Grid::new(48., 11., 0.5, 0.5, 3, vec![0.; 3], "test").is_err());
+    }
+
+    #[test]
+    fn look_up_tiles_in_order() {
+        let grid =
+            |west, elevation| Grid::new(48., west, 1., 1., 2, vec![elevation; 4], "SRTM").unwrap();
+        let tiles = Tiles::new(vec![grid(11., 100.), grid(12., 200.)]);
+
+        assert_close(tiles.elevation(47.5, 11.5), 100.);
+        assert_close(tiles.elevation(47.5, 12.5), 200.);
+        // On the shared edge the first tile is used
+        assert_close(tiles.elevation(47.5, 12.), 100.);
+        assert_eq!(tiles.elevation(47.5, 13.5), None);
+        assert_eq!(tiles.source(), "SRTM");
+    }
+}
@@ -0,0 +1,218 @@
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek},
+    path::Path,
+};
+
+use anyhow::{ensure, Error};
+use tiff::{
+    decoder::{Decoder, DecodingResult},
+    tags::Tag,
+};
+
+use crate::dem::Grid;
+
+/// GeoKey of the kind of coordinate system
+const MODEL_TYPE_KEY: u16 = 1024;
+/// Latitudes and longitudes
+const MODEL_TYPE_GEOGRAPHIC: u16 = 2;
+/// GeoKey of whether a pixel covers an area or is a point
+const RASTER_TYPE_KEY: u16 = 1025;
+const RASTER_PIXEL_IS_POINT: u16 = 2;
+
+/// Read a GeoTIFF, the source is the name of the file
+pub fn read_geotiff(path: &Path) -> Result<Grid, Error> {
+    let source = path
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+
+    parse_geotiff(BufReader::new(File::open(path)?), &source)
+}
+
+/// Parse a single band GeoTIFF of elevations (in metres) in geographic coordinates
+pub fn parse_geotiff<R>(reader: R, source: &str) -> Result<Grid, Error>
+where
+    R: Read + Seek,
+{
+    let mut decoder = Decoder::new(reader)?;
+    let (width, _) = decoder.dimensions()?;
+    let bands: u16 = decoder
+        .find_tag_unsigned(Tag::SamplesPerPixel)?
+        .unwrap_or(1);
+    ensure!(
+        bands == 1,
+        "Only single band GeoTIFFs are supported, this one has {bands} bands"
+    );
+
+    let keys: Vec<u16> = decoder
+        .find_tag_unsigned_vec(Tag::GeoKeyDirectoryTag)?
+        .unwrap_or_default();
+    // A header of four values followed by keys of four values each, the value of a key is
+    // stored in place if it has no separate location
+    let geo_key = |id: u16| {
+        keys.chunks_exact(4)
+            .skip(1)
+            .find(|key| key[0] == id && key[1] == 0)
+            .map(|key| key[3])
+    };
+    ensure!(
+        geo_key(MODEL_TYPE_KEY).is_none_or(|kind| kind == MODEL_TYPE_GEOGRAPHIC),
+        "Only GeoTIFFs in geographic coordinates are supported"
+    );
+    let is_point = geo_key(RASTER_TYPE_KEY) == Some(RASTER_PIXEL_IS_POINT);
+
+    let scale = decoder.get_tag_f64_vec(Tag::ModelPixelScaleTag)?;
+    let tiepoint = decoder.get_tag_f64_vec(Tag::ModelTiepointTag)?;
+    ensure!(
+        scale.len() >= 2 && tiepoint.len() >= 6,
+        "Expected a pixel scale and a tiepoint in the GeoTIFF"
+    );
+    let no_data: Option<f64> = match decoder.find_tag(Tag::GdalNodata)? {
+        Some(value) => value
+            .into_string()?
+            .trim_end_matches('\0')
+            .trim()
+            .parse()
+            .ok(),
+        None => None,
+    };
+
+    let elevations: Vec<f64> = match decoder.read_image()? {
+        DecodingResult::U8(values) => values.into_iter().map(f64::from).collect(),
+        DecodingResult::U16(values) => values.into_iter().map(f64::from).collect(),
+        DecodingResult::U32(values) => values.into_iter().map(f64::from).collect(),
+        DecodingResult::U64(values) => values.into_iter().map(|value| value as f64).collect(),
+        DecodingResult::I8(values) => values.into_iter().map(f64::from).collect(),
+        DecodingResult::I16(values) => values.into_iter().map(f64::from).collect(),
+        DecodingResult::I32(values) => values.into_iter().map(f64::from).collect(),
+        DecodingResult::I64(values) => values.into_iter().map(|value| value as f64).collect(),
+        DecodingResult::F32(values) => values.into_iter().map(f64::from).collect(),
+        DecodingResult::F64(values) => values,
+    };
+    let elevations = elevations
+        .into_iter()
+        .map(|elevation| {
+            if Some(elevation) == no_data {
+                f64::NAN
+            } else {
+                elevation
+            }
+        })
+        .collect();
+
+    // The tiepoint ties the raster position (i, j) to the longitude and latitude (x, y)
+    let (lon_step, lat_step) = (scale[0], scale[1]);
+    let mut west = tiepoint[3] - tiepoint[0] * lon_step;
+    let mut north = tiepoint[4] + tiepoint[1] * lat_step;
+    if !is_point {
+        // The raster position is the corner of the pixel, the elevation is at its centre
+        west += lon_step / 2.;
+        north -= lat_step / 2.;
+    }
+
+    Grid::new(
+        north,
+        west,
+        lat_step,
+        lon_step,
+        width as usize,
+        elevations,
+        source,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use tiff::encoder::{
+        colortype::{GrayI16, RGB8},
+        TiffEncoder,
+    };
+
+    use super::*;
+    use crate::dem::ElevationModel;
+
+    fn assert_close(is: Option<f64>, want: f64) {
+        let is = is.unwrap();
+        assert!((is - want).abs() < 1e-6, "{is} != {want}");
+    }
+
+    fn encode(keys: &[u16]) -> Cursor<Vec<u8>> {
+        let mut bytes = Cursor::new(Vec::new());
+        let mut encoder = TiffEncoder::new(&mut bytes).unwrap();
+        let mut image = encoder.new_image::<GrayI16>(3, 2).unwrap();
+
+        // Pixels of a tenth of a degree, the north-western corner at 48° N 11° E
+        image
+            .encoder()
+            .write_tag(Tag::ModelPixelScaleTag, &[0.1, 0.1, 0.][..])
+            .unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::ModelTiepointTag, &[0., 0., 0., 11., 48., 0.][..])
+            .unwrap();
+        image
+            .encoder()
+            .write_tag(Tag::GeoKeyDirectoryTag, keys)
+            .unwrap();
+        image.encoder().write_tag(Tag::GdalNodata, "-9999").unwrap();
+        image.write_data(&[100, 200, 300, 400, 500, -9999]).unwrap();
+
+        bytes.set_position(0);
+        bytes
+    }
+
+    #[test]
+    fn parse_pixels_as_areas() {
+        let keys = [
+            1,
+            1,
+            0,
+            2,
+            MODEL_TYPE_KEY,
+            0,
+            1,
+            MODEL_TYPE_GEOGRAPHIC,
+            RASTER_TYPE_KEY,
+            0,
+            1,
+            1,
+        ];
+        let grid = parse_geotiff(encode(&keys), "test.tif").unwrap();
+
+        assert_close(grid.elevation(47.95, 11.05), 100.);
+        assert_close(grid.elevation(47.85, 11.15), 500.);
+        assert_eq!(grid.elevation(47.85, 11.25), None);
+        assert_eq!(grid.elevation(48., 11.05), None);
+        assert_eq!(grid.source(), "test.tif");
+    }
+
+    #[test]
+    fn parse_pixels_as_points() {
+        let keys = [1, 1, 0, 1, RASTER_TYPE_KEY, 0, 1, RASTER_PIXEL_IS_POINT];
+        let grid = parse_geotiff(encode(&keys), "test.tif").unwrap();
+
+        assert_close(grid.elevation(48., 11.), 100.);
+        assert_close(grid.elevation(47.95, 11.05), 300.);
+    }
+
+    #[test]
+    fn reject_projected_coordinates() {
+        let keys = [1, 1, 0, 1, MODEL_TYPE_KEY, 0, 1, 1];
+        assert!(parse_geotiff(encode(&keys), "test.tif").is_err());
+    }
+
+    #[test]
+    fn reject_multiple_bands() {
+        let mut bytes = Cursor::new(Vec::new());
+        TiffEncoder::new(&mut bytes)
+            .unwrap()
+            .write_image::<RGB8>(1, 1, &[1, 2, 3])
+            .unwrap();
+        bytes.set_position(0);
+
+        let err = parse_geotiff(bytes, "test.tif").unwrap_err();
+        assert!(err.to_string().contains("single band"), "{err}");
+    }
+}
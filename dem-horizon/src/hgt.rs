@@ -0,0 +1,105 @@
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, bail, ensure, Error};
+
+use crate::dem::Grid;
+
+/// Value of the samples without data, e.g. in deep valleys shadowed from the radar
+const VOID: i16 = -32768;
+
+/// Read an SRTM tile, named after its south-western corner like `N47E011.hgt`
+pub fn read_hgt(path: &Path) -> Result<Grid, Error> {
+    let name = path
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .ok_or(anyhow!("Invalid name of SRTM tile '{}'", path.display()))?;
+
+    parse_hgt(name, &fs::read(path)?)
+}
+
+/// Parse an SRTM tile, a square of big endian 16 bit elevations (in metres) covering one
+/// degree with the rows from north to south
+pub fn parse_hgt(name: &str, bytes: &[u8]) -> Result<Grid, Error> {
+    let (south, west) = tile_corner(name)?;
+
+    let size = ((bytes.len() / 2) as f64).sqrt().round() as usize;
+    ensure!(
+        size >= 2 && size * size * 2 == bytes.len(),
+        "Expected a square of elevations in SRTM tile '{name}', had {} bytes",
+        bytes.len()
+    );
+
+    let elevations = bytes
+        .chunks_exact(2)
+        .map(|pair| match i16::from_be_bytes([pair[0], pair[1]]) {
+            VOID => f64::NAN,
+            elevation => f64::from(elevation),
+        })
+        .collect();
+
+    // Neighbouring tiles share their edges
+    let step = 1. / (size - 1) as f64;
+    let source = match size {
+        3601 => "SRTM 1 arc-second".to_string(),
+        1201 => "SRTM 3 arc-second".to_string(),
+        _ => format!("SRTM {size}x{size}"),
+    };
+
+    Grid::new(south + 1., west, step, step, size, elevations, source)
+}
+
+/// Latitude and longitude of the south-western corner of a tile from its name
+fn tile_corner(name: &str) -> Result<(f64, f64), Error> {
+    let name = name.to_ascii_uppercase();
+    if name.len() != 7 || !name.is_ascii() {
+        bail!("Expected an SRTM tile name like 'N47E011', had '{name}'");
+    }
+
+    let lat: f64 = name[1..3].parse()?;
+    let lon: f64 = name[4..7].parse()?;
+    let lat = match &name[..1] {
+        "N" => lat,
+        "S" => -lat,
+        _ => bail!("Expected N or S at the start of SRTM tile name '{name}'"),
+    };
+    let lon = match &name[3..4] {
+        "E" => lon,
+        "W" => -lon,
+        _ => bail!("Expected E or W in SRTM tile name '{name}'"),
+    };
+
+    Ok((lat, lon))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dem::ElevationModel;
+
+    #[test]
+    fn parse_tile_names() {
+        assert_eq!(tile_corner("N47E011").unwrap(), (47., 11.));
+        assert_eq!(tile_corner("s33w071").unwrap(), (-33., -71.));
+        assert!(tile_corner("N47E11").is_err());
+        assert!(tile_corner("X47E011").is_err());
+    }
+
+    #[test]
+    fn parse_tile() {
+        // 3 by 3 samples, half a degree apart
+        let elevations: [i16; 9] = [0, 100, 200, 300, 400, 500, 600, 700, VOID];
+        let bytes: Vec<u8> = elevations
+            .iter()
+            .flat_map(|elevation| elevation.to_be_bytes())
+            .collect();
+
+        let grid = parse_hgt("N47E011", &bytes).unwrap();
+        assert_eq!(grid.elevation(48., 11.), Some(0.));
+        assert_eq!(grid.elevation(47.5, 11.5), Some(400.));
+        assert_eq!(grid.elevation(47., 11.), Some(600.));
+        assert_eq!(grid.elevation(47., 12.), None);
+        assert_eq!(grid.source(), "SRTM 3x3");
+
+        assert!(parse_hgt("N47E011", &bytes[..16]).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use std::f64::consts::TAU;
+
+use anyhow::{anyhow, ensure, Error};
+use chrono::{DateTime, Utc};
+
+use sky_service::{
+    horizon::HorizonMetadata,
+    location::{apparent_altitude, EARTH_RADIUS, TERRESTRIAL_REFRACTION},
+    Horizon, Location, HORIZON_SAMPLES,
+};
+
+pub mod dem;
+pub mod geotiff;
+pub mod hgt;
+
+use dem::ElevationModel;
+
+/// How a horizon is computed from an elevation model
+#[derive(Debug, Clone, Copy)]
+pub struct HorizonOptions {
+    /// Number of equally spaced azimuths
+    pub samples: usize,
+    /// Farthest distance of the terrain which is taken into account (in metres)
+    pub radius: f64,
+    /// Distance between two samples of the terrain along an azimuth (in metres)
+    pub step: f64,
+    /// Height of the eyes of the observer above the ground (in metres)
+    pub height: f64,
+    /// Share of the curvature of the earth compensated by terrestrial refraction
+    pub refraction: f64,
+}
+
+impl Default for HorizonOptions {
+    fn default() -> Self {
+        Self {
+            samples: HORIZON_SAMPLES,
+            radius: 50_000.,
+            step: 30.,
+            height: 2.,
+            refraction: TERRESTRIAL_REFRACTION,
+        }
+    }
+}
+
+/// Compute the horizon seen from the point at `lat` and `lon` by marching outwards along each
+/// of the azimuths and keeping the highest apparent altitude of the terrain.
+///
+/// Where the model has no terrain along an azimuth, the horizon is the one of the sea.
+pub fn compute_horizon<M>(
+    model: &M,
+    lat: f64,
+    lon: f64,
+    options: &HorizonOptions,
+    created: DateTime<Utc>,
+) -> Result<Horizon, Error>
+where
+    M: ElevationModel,
+{
+    ensure!(
+        options.samples > 0 && options.step > 0.,
+        "A horizon needs at least one azimuth and a positive step"
+    );
+
+    let ground = model
+        .elevation(lat, lon)
+        .ok_or(anyhow!("The elevation model has no data at {lat}, {lon}"))?;
+    let elevation = ground + options.height;
+    let sea_level = -Location {
+        lat,
+        lon,
+        elevation,
+        ..Default::default()
+    }
+    .dip()
+    .0;
+
+    let steps = (options.radius / options.step).floor() as usize;
+    let altitudes: Vec<f64> = (0..options.samples)
+        .map(|i| {
+            let azimuth = TAU * i as f64 / options.samples as f64;
+
+            (1..=steps)
+                .filter_map(|step| {
+                    let distance = step as f64 * options.step;
+                    let (lat, lon) = destination(lat, lon, azimuth, distance);
+                    let terrain = model.elevation(lat, lon)?;
+
+                    Some(apparent_altitude(terrain - elevation, distance, options.refraction).0)
+                })
+                .fold(sea_level, f64::max)
+        })
+        .collect();
+
+    Ok(Horizon::new(altitudes).with_metadata(HorizonMetadata {
+        lat,
+        lon,
+        elevation,
+        source: model.source(),
+        created,
+    }))
+}
+
+/// The point `distance` metres from `lat` and `lon` in the direction of `azimuth` (in radians
+/// from north towards east) along a great circle
+pub fn destination(lat: f64, lon: f64, azimuth: f64, distance: f64) -> (f64, f64) {
+    let (lat, lon) = (lat.to_radians(), lon.to_radians());
+    let angle = distance / EARTH_RADIUS;
+
+    let lat2 = (lat.sin() * angle.cos() + lat.cos() * angle.sin() * azimuth.cos()).asin();
+    let lon2 =
+        lon + (azimuth.sin() * angle.sin() * lat.cos()).atan2(angle.cos() - lat.sin() * lat2.sin());
+
+    (lat2.to_degrees(), lon2.to_degrees())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn destination_along_great_circles() {
+        let location = Location {
+            lat: 47.,
+            lon: 11.,
+            ..Default::default()
+        };
+
+        for azimuth in [0., 1., 2., 3.5, 5.] {
+            let (lat, lon) = destination(47., 11., azimuth, 10_000.);
+            assert!((location.distance(lat, lon) - 10_000.).abs() < 1e-6);
+        }
+
+        // One degree of latitude to the north
+        let (lat, lon) = destination(47., 11., 0., EARTH_RADIUS * 1f64.to_radians());
+        assert!((lat - 48.).abs() < 1e-9 && (lon - 11.).abs() < 1e-9);
+    }
+}
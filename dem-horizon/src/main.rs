@@ -0,0 +1,50 @@
+use std::{env, fs, process};
+
+use anyhow::{anyhow, Error};
+use chrono::Utc;
+
+use dem_horizon::{compute_horizon, dem::Tiles, HorizonOptions};
+use sky_service::horizon::HorizonUnit;
+
+const USAGE: &str = "Usage: dem-horizon [--legacy] <lat> <lon> <output> <tile.hgt|tile.tif>...";
+/// Write the legacy format of 1024 altitudes without a header instead
+const LEGACY_FLAG: &str = "--legacy";
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("{err}\n{USAGE}");
+        process::exit(1);
+    }
+}
+
+/// Compute the horizon at a point from local tiles and write it in the binary format
+fn run() -> Result<(), Error> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (legacy, args) = match args.split_first() {
+        Some((flag, rest)) if flag == LEGACY_FLAG => (true, rest),
+        _ => (false, args.as_slice()),
+    };
+    let [lat, lon, output, tiles @ ..] = args else {
+        return Err(anyhow!("Missing arguments"));
+    };
+    if tiles.is_empty() {
+        return Err(anyhow!("Missing elevation model"));
+    }
+
+    let model = Tiles::open(tiles)?;
+    let horizon = compute_horizon(
+        &model,
+        lat.parse()?,
+        lon.parse()?,
+        &HorizonOptions::default(),
+        Utc::now(),
+    )?;
+    let bytes = if legacy {
+        horizon.encode_legacy()
+    } else {
+        horizon.encode(HorizonUnit::Radians)
+    };
+    fs::write(output, bytes)?;
+
+    Ok(())
+}
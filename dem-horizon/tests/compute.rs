@@ -0,0 +1,128 @@
+use std::{env, fs};
+
+use chrono::DateTime;
+
+use dem_horizon::{
+    compute_horizon,
+    dem::{ElevationModel, Grid, Tiles},
+    HorizonOptions,
+};
+use sky_service::{
+    angle::{Degrees, Radians},
+    horizon::HorizonUnit,
+    location::{apparent_altitude, TERRESTRIAL_REFRACTION},
+    Horizon, Location,
+};
+
+/// Degrees of longitude per metre at 47° N
+const LON_PER_METRE: f64 = 1. / 75_840.;
+
+/// A plain at 500 m with a wall rising to 1500 m from 5 km west of 11° E
+fn plain_with_wall() -> Grid {
+    let (rows, columns) = (201, 301);
+    let elevations = (0..rows * columns)
+        .map(|i| {
+            let lon = 10.85 + (i % columns) as f64 * 0.001;
+            if lon < 11. - 5000. * LON_PER_METRE {
+                1500.
+            } else {
+                500.
+            }
+        })
+        .collect();
+
+    Grid::new(47.1, 10.85, 0.001, 0.001, columns, elevations, "synthetic").unwrap()
+}
+
+fn options() -> HorizonOptions {
+    HorizonOptions {
+        samples: 64,
+        radius: 10_000.,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn wall_in_the_west() {
+    let grid = plain_with_wall();
+    let created = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+    let horizon = compute_horizon(&grid, 47., 11., &options(), created).unwrap();
+
+    assert_eq!(horizon.samples(), 64);
+    let metadata = horizon.metadata().unwrap();
+    assert_eq!(metadata.elevation, 502.);
+    assert_eq!(metadata.source, "synthetic");
+
+    // The top of the wall, 998 m above the eyes of the observer and about 5 km away
+    let west = horizon.altitude_at(Degrees(270.).to_radians());
+    let want = (998f64 / 5000.).atan();
+    assert!(
+        (west.0 - want).abs() < Degrees(0.3).to_radians().0,
+        "{west:?}"
+    );
+
+    // Across the plain the curvature of the earth lets the ground fall away slightly
+    for azimuth in [0., 90., 180.] {
+        let altitude = horizon.altitude_at(Degrees(azimuth).to_radians());
+        assert!(
+            (Degrees(-0.1).to_radians()..Radians(0.)).contains(&altitude),
+            "{altitude:?} at {azimuth}°"
+        );
+    }
+
+    // The encoded horizon is read back by the sky service
+    for unit in [HorizonUnit::Radians, HorizonUnit::Degrees] {
+        let decoded = Horizon::try_from(horizon.encode(unit)).unwrap();
+        assert_eq!(decoded.metadata(), horizon.metadata());
+        assert!((decoded.altitude_at(Degrees(270.).to_radians()) - west).abs() < Radians(1e-12));
+    }
+
+    let location = Location {
+        lat: 47.,
+        lon: 11.,
+        ..Default::default()
+    };
+    assert!(horizon.check(&location, Some(created)).is_ok());
+}
+
+#[test]
+fn read_tile_from_disk() {
+    // A tile of 121 by 121 samples with a cone 1000 m high in its centre
+    let size = 121;
+    let bytes: Vec<u8> = (0..size * size)
+        .flat_map(|i| {
+            let (row, column) = ((i / size) as f64, (i % size) as f64);
+            let distance = (row - 60.).hypot(column - 60.);
+            let elevation = (1000. - 50. * distance).max(0.) as i16;
+            elevation.to_be_bytes()
+        })
+        .collect();
+
+    let path = env::temp_dir().join(format!("dem-horizon-{}", std::process::id()));
+    fs::create_dir_all(&path).unwrap();
+    let tile = path.join("N47E011.hgt");
+    fs::write(&tile, bytes).unwrap();
+
+    let tiles = Tiles::open(&[&tile]).unwrap();
+    fs::remove_dir_all(&path).unwrap();
+
+    assert_eq!(tiles.elevation(47.5, 11.5), Some(1000.));
+    assert_eq!(tiles.source(), "SRTM 121x121");
+
+    // On the southern flank of the cone at 400 m, its summit lies 11.1 km due north
+    let options = HorizonOptions {
+        radius: 20_000.,
+        ..options()
+    };
+    let horizon = compute_horizon(&tiles, 47.4, 11.5, &options, Default::default()).unwrap();
+    let north = horizon.altitude_at(Radians(0.));
+    let want = apparent_altitude(598., 11_120., TERRESTRIAL_REFRACTION);
+    assert!(
+        (north - want).abs() < Degrees(0.05).to_radians(),
+        "{north:?}"
+    );
+
+    // Down the flank the ground falls away
+    let south = horizon.altitude_at(Degrees(180.).to_radians());
+    assert!(south < Radians(0.), "{south:?}");
+}
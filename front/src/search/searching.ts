@@ -38,10 +38,16 @@ subscription spot($time: DateTime!, $timezone: TimeZone!, $lat: Float!, $lon: Fl
       kind
       events {
         sun {
-          ${horizonEventsCollectionQuery}
+          state
+          events {
+            ${horizonEventsCollectionQuery}
+          }
         }
         moon {
-          ${horizonEventsCollectionQuery}
+          state
+          events {
+            ${horizonEventsCollectionQuery}
+          }
         }
       }
     }
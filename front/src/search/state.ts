@@ -14,6 +14,11 @@ export interface HorizonEventCollection {
     set: HorizonEvent;
 }
 
+export interface HorizonEventsResult {
+    state: "EVENTS" | "ALWAYS_UP" | "ALWAYS_DOWN" | "SOLVER_FAILED";
+    events?: HorizonEventCollection;
+}
+
 export interface Result {
     kind: string;
     location: {
@@ -21,8 +26,8 @@ export interface Result {
         lon: number;
     };
     events: {
-        sun?: HorizonEventCollection;
-        moon?: HorizonEventCollection;
+        sun: HorizonEventsResult;
+        moon: HorizonEventsResult;
     };
 }
 
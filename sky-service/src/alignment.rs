@@ -0,0 +1,242 @@
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    angle::{AngleExtensions, Degrees, Radians},
+    crossing::{find_crossings, Crossing, EventKind, MIN_CROSSING_SECONDS},
+    location::{apparent_altitude, TERRESTRIAL_REFRACTION},
+    Horizon, HorizonEvent, Location, SkyObject, SkyPosition,
+};
+
+/// Events closer than this in time are considered the same, e.g. a moonrise
+/// found when searching from two consecutive days
+const SAME_EVENT_SECONDS: i64 = 60;
+
+/// Interval at which the distance between an object and a landmark is sampled.
+/// The sun and the moon move less than 1.5° in this time.
+const LANDMARK_STEP_SECONDS: i64 = 300;
+
+/// A range of azimuths, from `start` clockwise to `end`.
+///
+/// The range contains north if `end` is less than `start`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AzimuthRange {
+    pub start: Radians,
+    pub end: Radians,
+}
+
+impl AzimuthRange {
+    pub fn contains(&self, azimuth: Radians) -> bool {
+        if (self.end - self.start).abs() >= Degrees(360.).to_radians() {
+            return true;
+        }
+
+        (azimuth - self.start).normalize() <= (self.end - self.start).normalize()
+    }
+}
+
+/// A rise or set of an object within an azimuth range
+#[derive(Serialize, Deserialize)]
+pub struct AzimuthMatch {
+    pub kind: EventKind,
+    pub event: HorizonEvent,
+}
+
+/// Find the rises and sets of the object within the azimuth range for `days` consecutive days
+/// starting at `time`, in chronological order.
+pub fn find_events_in_azimuth_range<O>(
+    object: &O,
+    time: &NaiveDateTime,
+    days: u32,
+    location: &Location,
+    horizon: &Horizon,
+    range: &AzimuthRange,
+) -> Vec<AzimuthMatch>
+where
+    O: SkyObject,
+{
+    let mut matches: Vec<AzimuthMatch> = Vec::new();
+    let min_duration = Duration::try_seconds(MIN_CROSSING_SECONDS).expect("constant value");
+
+    for day in 0..days {
+        let start = *time + Duration::try_days(day.into()).expect("days fit into a duration");
+        let end = start + object.period();
+        // Behind a ragged horizon the object may set into a notch or reappear behind a ridge,
+        // so every crossing of the day is a candidate and not only the first rise and set
+        let crossings = find_crossings(
+            object,
+            &start,
+            &end,
+            location,
+            horizon,
+            Radians(0.),
+            min_duration,
+        );
+
+        for Crossing { kind, event } in crossings {
+            let is_known = matches.iter().any(|known| {
+                known.kind == kind
+                    && (known.event.time - event.time).num_seconds().abs() < SAME_EVENT_SECONDS
+            });
+
+            if range.contains(event.azimuth) && !is_known {
+                matches.push(AzimuthMatch { kind, event });
+            }
+        }
+    }
+
+    matches.sort_by_key(|azimuth_match| azimuth_match.event.time);
+    matches
+}
+
+/// A landmark like a summit cross or a tower which an object can pass behind
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Landmark {
+    pub lat: f64,
+    pub lon: f64,
+    /// Elevation of the ground at the landmark above sea level (in metres)
+    pub elevation: f64,
+    /// Height of the landmark above the ground (in metres)
+    #[serde(default)]
+    pub height: f64,
+}
+
+impl Landmark {
+    /// Where the top of the landmark appears in the sky as seen from the location
+    pub fn target(&self, location: &Location) -> SkyPosition {
+        let (lat1, lon1) = (location.lat.to_radians(), location.lon.to_radians());
+        let (lat2, lon2) = (self.lat.to_radians(), self.lon.to_radians());
+        let delta_lon = lon2 - lon1;
+
+        let azimuth = (delta_lon.sin() * lat2.cos())
+            .atan2(lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * delta_lon.cos())
+            .normalize_radians();
+
+        let distance = location.distance(self.lat, self.lon);
+
+        let height = self.elevation + self.height - location.elevation;
+
+        SkyPosition {
+            altitude: apparent_altitude(height, distance, TERRESTRIAL_REFRACTION),
+            azimuth: Radians(azimuth),
+        }
+    }
+}
+
+/// Angle between two positions in the sky
+pub fn angular_distance(a: &SkyPosition, b: &SkyPosition) -> Radians {
+    let cos = a.altitude.sin() * b.altitude.sin()
+        + a.altitude.cos() * b.altitude.cos() * (a.azimuth - b.azimuth).cos();
+
+    Radians(cos.clamp(-1., 1.).acos())
+}
+
+/// Find the times at which the object passes within `max_distance` of the top of
+/// the landmark for `days` consecutive days starting at `time`, in chronological order.
+///
+/// Each event is the moment of the closest approach, with the position of the object.
+pub fn find_landmark_alignments<O>(
+    object: &O,
+    time: &NaiveDateTime,
+    days: u32,
+    location: &Location,
+    landmark: &Landmark,
+    max_distance: Radians,
+) -> Vec<HorizonEvent>
+where
+    O: SkyObject,
+{
+    let target = landmark.target(location);
+    let distance_at =
+        |time: &NaiveDateTime| angular_distance(&object.position(time, location), &target).0;
+
+    let step = Duration::try_seconds(LANDMARK_STEP_SECONDS).expect("constant value");
+    let samples = i64::from(days) * 86400 / LANDMARK_STEP_SECONDS;
+    let distances: Vec<(NaiveDateTime, f64)> = (0..=samples)
+        .map(|i| {
+            let time = *time + step * i as i32;
+            (time, distance_at(&time))
+        })
+        .collect();
+
+    distances
+        .windows(3)
+        .filter(|window| window[1].1 <= window[0].1 && window[1].1 < window[2].1)
+        .filter_map(|window| {
+            let time = crate::find_minimum(&distance_at, window[0].0, window[2].0);
+            (distance_at(&time) <= max_distance.0).then(|| {
+                let SkyPosition { altitude, azimuth } = object.position(&time, location);
+
+                HorizonEvent {
+                    time: time.and_utc(),
+                    altitude,
+                    azimuth,
+                }
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn landmark_target() {
+        let location = Location {
+            lat: 47.,
+            lon: 11.,
+            elevation: 1000.,
+            ..Default::default()
+        };
+
+        // A tower about 1 km to the north, its top 100 m above the observer
+        let tower = Landmark {
+            lat: 47.009,
+            lon: 11.,
+            elevation: 1050.,
+            height: 50.,
+        };
+        let target = tower.target(&location);
+        let Degrees(azimuth) = target.azimuth.to_degrees();
+        assert!(azimuth.min(360. - azimuth) < 1e-6);
+        assert!((target.altitude.to_degrees().0 - 5.71).abs() < 0.01);
+
+        // A summit about 10 km to the east at the same elevation sinks behind the curvature
+        let summit = Landmark {
+            lat: 47.,
+            lon: 11.1316,
+            elevation: 1000.,
+            height: 0.,
+        };
+        let target = summit.target(&location);
+        assert!((target.azimuth.to_degrees().0 - 90.).abs() < 0.1);
+        assert!((target.altitude.to_degrees().0 - -0.039).abs() < 0.001);
+    }
+
+    #[test]
+    fn azimuth_range_contains() {
+        let west = AzimuthRange {
+            start: Degrees(250.).to_radians(),
+            end: Degrees(290.).to_radians(),
+        };
+        assert!(west.contains(Degrees(270.).to_radians()));
+        assert!(west.contains(Degrees(250.).to_radians()));
+        assert!(!west.contains(Degrees(90.).to_radians()));
+
+        let north = AzimuthRange {
+            start: Degrees(350.).to_radians(),
+            end: Degrees(10.).to_radians(),
+        };
+        assert!(north.contains(Radians(0.)));
+        assert!(north.contains(Degrees(355.).to_radians()));
+        assert!(north.contains(Degrees(5.).to_radians()));
+        assert!(!north.contains(Degrees(180.).to_radians()));
+
+        let all = AzimuthRange {
+            start: Radians(0.),
+            end: Degrees(360.).to_radians(),
+        };
+        assert!(all.contains(Degrees(180.).to_radians()));
+    }
+}
@@ -1,4 +1,148 @@
 use std::f64::consts::TAU;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use serde::{Deserialize, Serialize};
+
+/// An angle in radians, e.g. an altitude or an azimuth measured from north towards east.
+///
+/// All angles in positions, horizons, events and messages are given in radians.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Radians(pub f64);
+
+/// An angle in degrees, for input and output meant to be read by people
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Degrees(pub f64);
+
+macro_rules! angle_ops {
+    ($angle:ident) => {
+        impl $angle {
+            pub fn max(self, other: Self) -> Self {
+                Self(self.0.max(other.0))
+            }
+
+            pub fn min(self, other: Self) -> Self {
+                Self(self.0.min(other.0))
+            }
+
+            pub fn abs(self) -> Self {
+                Self(self.0.abs())
+            }
+        }
+
+        impl Add for $angle {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self(self.0 + other.0)
+            }
+        }
+
+        impl Sub for $angle {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self(self.0 - other.0)
+            }
+        }
+
+        impl Neg for $angle {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self(-self.0)
+            }
+        }
+
+        impl Mul<f64> for $angle {
+            type Output = Self;
+
+            fn mul(self, factor: f64) -> Self {
+                Self(self.0 * factor)
+            }
+        }
+
+        impl Div<f64> for $angle {
+            type Output = Self;
+
+            fn div(self, divisor: f64) -> Self {
+                Self(self.0 / divisor)
+            }
+        }
+
+        /// The ratio of two angles
+        impl Div for $angle {
+            type Output = f64;
+
+            fn div(self, divisor: Self) -> f64 {
+                self.0 / divisor.0
+            }
+        }
+    };
+}
+
+angle_ops!(Radians);
+angle_ops!(Degrees);
+
+impl Radians {
+    pub fn to_degrees(self) -> Degrees {
+        Degrees(self.0.to_degrees())
+    }
+
+    /// The same angle within `[0, 2π)`
+    pub fn normalize(self) -> Self {
+        Self(self.0.normalize_radians())
+    }
+
+    pub fn sin(self) -> f64 {
+        self.0.sin()
+    }
+
+    pub fn cos(self) -> f64 {
+        self.0.cos()
+    }
+
+    pub fn tan(self) -> f64 {
+        self.0.tan()
+    }
+}
+
+impl Degrees {
+    pub fn to_radians(self) -> Radians {
+        Radians(self.0.to_radians())
+    }
+
+    /// The same angle within `[0, 360)`
+    pub fn normalize(self) -> Self {
+        Self(self.0.normalize_degrees())
+    }
+}
+
+impl From<Degrees> for Radians {
+    fn from(angle: Degrees) -> Self {
+        angle.to_radians()
+    }
+}
+
+impl From<Radians> for Degrees {
+    fn from(angle: Radians) -> Self {
+        angle.to_degrees()
+    }
+}
+
+impl fmt::Display for Radians {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} rad", self.0)
+    }
+}
+
+impl fmt::Display for Degrees {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}°", self.0)
+    }
+}
 
 /// Extensions for normalizing angles
 pub trait AngleExtensions {
@@ -43,7 +187,10 @@ impl AngleExtensions for f64 {
 mod test {
     use std::f64::consts::TAU;
 
-    use crate::{angle::AngleExtensions, util::assert_precisely_eq};
+    use crate::{
+        angle::{AngleExtensions, Degrees, Radians},
+        util::assert_precisely_eq,
+    };
 
     #[test]
     fn normalize_radians() {
@@ -51,4 +198,16 @@ mod test {
         let high = want + 1000. * TAU;
         assert_precisely_eq(high.normalize_radians(), want);
     }
+
+    #[test]
+    fn convert_units() {
+        let west = Degrees(270.);
+        assert_precisely_eq(Radians::from(west).0, 1.5 * std::f64::consts::PI);
+        assert_precisely_eq(west.to_radians().to_degrees().0, 270.);
+        assert_precisely_eq((Radians(-1.) + Radians(TAU)).normalize().0, TAU - 1.);
+
+        // The wire format is the bare number
+        assert_eq!(serde_json::to_string(&Radians(0.5)).unwrap(), "0.5");
+        assert_eq!(serde_json::from_str::<Degrees>("90").unwrap(), Degrees(90.));
+    }
 }
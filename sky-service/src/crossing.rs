@@ -0,0 +1,195 @@
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    angle::{Degrees, Radians},
+    horizon_altitude, Horizon, HorizonEvent, Location, SkyObject, SkyPosition,
+};
+
+/// Shortest period above or below the horizon which is found by default (in seconds)
+pub const MIN_CROSSING_SECONDS: i64 = 60;
+
+/// Longest interval between two samples (in seconds)
+const MAX_STEP_SECONDS: i64 = 3600;
+/// Bounding the speed of the azimuth breaks down towards the zenith, terrain higher than this
+/// is treated as if it were this high
+const MAX_TERRAIN_ALTITUDE: Degrees = Degrees(85.);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Rise,
+    Set,
+}
+
+/// A time at which an object crosses the horizon
+pub struct Crossing {
+    pub kind: EventKind,
+    pub event: HorizonEvent,
+}
+
+/// Range of the altitudes of the horizon and its steepest slope
+struct HorizonBounds {
+    lowest: Radians,
+    highest: Radians,
+    /// Largest change of the altitude per change of the azimuth
+    slope: f64,
+}
+
+impl HorizonBounds {
+    fn new(horizon: &Horizon, location: &Location, offset: Radians) -> Self {
+        let sea_level = -location.dip();
+
+        Self {
+            lowest: horizon.lowest().max(sea_level) + offset,
+            highest: horizon.highest().max(sea_level) + offset,
+            slope: horizon.max_slope(),
+        }
+    }
+
+    /// Time (in seconds) during which an object moving at most `speed` radians per second
+    /// cannot cross the horizon, given its current `altitude` and `height` above the horizon
+    fn safe_time(&self, altitude: Radians, height: Radians, speed: f64) -> f64 {
+        if altitude > self.highest {
+            (altitude - self.highest).0 / speed
+        } else if altitude < self.lowest {
+            (self.lowest - altitude).0 / speed
+        } else {
+            // Within the band of the terrain, the horizon moves up and down beneath the object
+            // with its slope times the speed of the azimuth
+            let band = self.lowest.abs().max(self.highest.abs());
+            let band = band.min(MAX_TERRAIN_ALTITUDE.to_radians());
+            height.abs().0 / (speed * (1. + self.slope / band.cos()))
+        }
+    }
+}
+
+/// Find all times between `start` and `end` at which the object crosses a line `offset` above
+/// the horizon, in chronological order. Each event is the whole second from `start`
+/// closest to the crossing.
+///
+/// The samples are spaced by how fast the object and the horizon beneath it can approach each
+/// other, but never closer than `min_duration`. Every period above or below the line lasting
+/// at least `min_duration` is found, shorter ones may be missed.
+pub fn find_crossings<O>(
+    object: &O,
+    start: &NaiveDateTime,
+    end: &NaiveDateTime,
+    location: &Location,
+    horizon: &Horizon,
+    offset: Radians,
+    min_duration: Duration,
+) -> Vec<Crossing>
+where
+    O: SkyObject,
+{
+    let bounds = HorizonBounds::new(horizon, location, offset);
+    let speed = object.max_angular_speed();
+    let min_seconds = min_duration.num_seconds().max(1);
+
+    // Altitude of the object and its height above the line
+    let height_at = |time: &NaiveDateTime| {
+        let SkyPosition { altitude, azimuth } = object.position(time, location);
+        (
+            altitude,
+            altitude - horizon_altitude(horizon, location, azimuth) - offset,
+        )
+    };
+
+    let mut crossings = Vec::new();
+    let mut left = *start;
+    let (mut altitude, mut height) = height_at(&left);
+    while left < *end {
+        let seconds =
+            (bounds.safe_time(altitude, height, speed) as i64).clamp(min_seconds, MAX_STEP_SECONDS);
+        let step = Duration::try_seconds(seconds).expect("at most MAX_STEP_SECONDS");
+
+        let right = (left + step).min(*end);
+        let (right_altitude, right_height) = height_at(&right);
+
+        let up = height > Radians(0.);
+        if up != (right_height > Radians(0.)) {
+            let time = bisect(
+                &|time| height_at(time).1 .0,
+                (left, height.0),
+                (right, right_height.0),
+            );
+            let SkyPosition { altitude, azimuth } = object.position(&time, location);
+
+            crossings.push(Crossing {
+                kind: if up { EventKind::Set } else { EventKind::Rise },
+                event: HorizonEvent {
+                    time: time.and_utc(),
+                    altitude,
+                    azimuth,
+                },
+            });
+        }
+
+        left = right;
+        altitude = right_altitude;
+        height = right_height;
+    }
+
+    crossings
+}
+
+/// Bisection for the sign change of `height` between `left` and `right` down to one second,
+/// returning whichever of the last two samples is closer to the line
+fn bisect<F>(
+    height_at: &F,
+    (mut left, mut left_height): (NaiveDateTime, f64),
+    (mut right, mut right_height): (NaiveDateTime, f64),
+) -> NaiveDateTime
+where
+    F: Fn(&NaiveDateTime) -> f64,
+{
+    let left_up = left_height > 0.;
+    while (right - left).num_seconds() > 1 {
+        let middle =
+            left + Duration::try_seconds((right - left).num_seconds() / 2).expect("shorter");
+        let height = height_at(&middle);
+
+        if (height > 0.) == left_up {
+            (left, left_height) = (middle, height);
+        } else {
+            (right, right_height) = (middle, height);
+        }
+    }
+
+    if left_height.abs() < right_height.abs() {
+        left
+    } else {
+        right
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn safe_time_outside_of_the_terrain() {
+        let bounds = HorizonBounds {
+            lowest: Radians(0.),
+            highest: Degrees(10.).to_radians(),
+            slope: 2.,
+        };
+        let speed = 15f64.to_radians() / 3600.;
+
+        // 20° above the highest terrain takes at least 80 minutes to reach it
+        let time = bounds.safe_time(Degrees(30.).to_radians(), Degrees(20.).to_radians(), speed);
+        assert!((time - 4800.).abs() < 1e-6);
+
+        let time = bounds.safe_time(
+            Degrees(-15.).to_radians(),
+            Degrees(-15.).to_radians(),
+            speed,
+        );
+        assert!((time - 3600.).abs() < 1e-6);
+
+        // Within the terrain, the slope shortens the time
+        let time = bounds.safe_time(Degrees(5.).to_radians(), Degrees(1.).to_radians(), speed);
+        assert!(time < 240. / 3.);
+    }
+}
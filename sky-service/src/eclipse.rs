@@ -0,0 +1,348 @@
+use std::f64::consts::PI;
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    calculate_visibility_intervals, find_minimum, julian,
+    sky::{
+        moon::meeus::{self, MeeusMoon, EARTH_RADIUS, MOON_RADIUS},
+        sun::vsop87::{self, Vsop87Sun, SUN_SEMIDIAMETER},
+        util,
+    },
+    Horizon, Location, SkyObject, VisibilityInterval,
+};
+
+const ASTRONOMICAL_UNIT: f64 = 149_597_870.7; // km
+
+/// Enlargement of the shadow of the earth by its atmosphere
+///
+/// source: Explanatory Supplement to the Astronomical Almanac, chapter 8
+const SHADOW_ENLARGEMENT: f64 = 1.02;
+/// Mean radius of the earth in units of its equatorial radius, for the shadow cone
+const SHADOW_EARTH_RADIUS: f64 = 0.99834;
+
+/// Interval at which the distance between the centres is sampled
+const SCAN_STEP_HOURS: i64 = 1;
+/// Samples at which the centres are farther apart cannot be close to an eclipse
+const SCAN_MAX_SEPARATION: f64 = 3. * PI / 180.;
+/// Longest time between the greatest eclipse and its first or last contact
+const CONTACT_WINDOW_HOURS: i64 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EclipseKind {
+    Solar,
+    Lunar,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhaseKind {
+    /// The moon is within the penumbra of the earth
+    Penumbral,
+    Partial,
+    /// The moon is within the disc of the sun
+    Annular,
+    Total,
+}
+
+/// A phase of an eclipse between two contacts
+#[derive(Serialize, Deserialize)]
+pub struct EclipsePhase {
+    pub kind: PhaseKind,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Parts of the phase during which the eclipsed object is above the horizon
+    pub visible: Vec<VisibilityInterval>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Eclipse {
+    pub kind: EclipseKind,
+    /// Time of the greatest eclipse
+    pub maximum: DateTime<Utc>,
+    /// Phases from the outermost to the innermost
+    pub phases: Vec<EclipsePhase>,
+}
+
+impl Eclipse {
+    /// The innermost phase, which gives the eclipse its name
+    pub fn phase(&self) -> Option<PhaseKind> {
+        self.phases.last().map(|phase| phase.kind)
+    }
+
+    /// Whether any phase of the eclipse happens above the horizon
+    pub fn is_visible(&self) -> bool {
+        self.phases.iter().any(|phase| !phase.visible.is_empty())
+    }
+}
+
+type Vector = [f64; 3];
+
+fn rectangular(alpha: f64, delta: f64, distance: f64) -> Vector {
+    let (alpha_sin, alpha_cos) = alpha.sin_cos();
+    let (delta_sin, delta_cos) = delta.sin_cos();
+
+    [
+        distance * delta_cos * alpha_cos,
+        distance * delta_cos * alpha_sin,
+        distance * delta_sin,
+    ]
+}
+
+fn length(a: &Vector) -> f64 {
+    a.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+fn angle_between(a: &Vector, b: &Vector) -> f64 {
+    let cross = [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ];
+    let dot: f64 = a.iter().zip(b).map(|(a, b)| a * b).sum();
+
+    length(&cross).atan2(dot)
+}
+
+/// Geocentric positions (in km) of the sun and the moon, in the frame of the mean equinox
+/// used by the sidereal time
+fn geocentric_positions(time: &NaiveDateTime) -> (Vector, Vector) {
+    let jde = julian::ephemeris_day_of(time);
+    let sun = vsop87::apparent_coordinates(jde);
+    let moon = meeus::apparent_coordinates(jde);
+
+    (
+        rectangular(
+            sun.alpha - sun.equation_of_equinoxes,
+            sun.delta,
+            sun.r * ASTRONOMICAL_UNIT,
+        ),
+        rectangular(
+            moon.alpha - moon.equation_of_equinoxes,
+            moon.delta,
+            moon.distance,
+        ),
+    )
+}
+
+/// Geocentric position of the observer (in km)
+fn observer_position(time: &NaiveDateTime, location: &Location) -> Vector {
+    let theta = util::sidereal_time(time, location, 0.);
+    let (rho_sin, rho_cos) =
+        util::observer_geocentric(location.lat.to_radians(), location.elevation);
+    let (theta_sin, theta_cos) = theta.sin_cos();
+
+    [
+        EARTH_RADIUS * rho_cos * theta_cos,
+        EARTH_RADIUS * rho_cos * theta_sin,
+        EARTH_RADIUS * rho_sin,
+    ]
+}
+
+/// Distance between the centres of the eclipsing and the eclipsed disc and, for each phase,
+/// the distance below which it takes place (all in radians)
+struct Circumstances {
+    separation: f64,
+    phases: Vec<(PhaseKind, f64)>,
+}
+
+/// The moon in front of the sun as seen by the observer
+fn solar_circumstances(time: &NaiveDateTime, location: &Location) -> Circumstances {
+    let (sun, moon) = geocentric_positions(time);
+    let observer = observer_position(time, location);
+    let sun = [0, 1, 2].map(|i| sun[i] - observer[i]);
+    let moon = [0, 1, 2].map(|i| moon[i] - observer[i]);
+
+    let sun_radius = SUN_SEMIDIAMETER * ASTRONOMICAL_UNIT / length(&sun);
+    let moon_radius = (MOON_RADIUS / length(&moon)).asin();
+    let central = if moon_radius > sun_radius {
+        PhaseKind::Total
+    } else {
+        PhaseKind::Annular
+    };
+
+    Circumstances {
+        separation: angle_between(&sun, &moon),
+        phases: vec![
+            (PhaseKind::Partial, sun_radius + moon_radius),
+            (central, (sun_radius - moon_radius).abs()),
+        ],
+    }
+}
+
+/// The moon in the shadow of the earth, the same for all observers
+fn lunar_circumstances(time: &NaiveDateTime) -> Circumstances {
+    let (sun, moon) = geocentric_positions(time);
+    let shadow = sun.map(|x| -x);
+
+    let sun_radius = SUN_SEMIDIAMETER * ASTRONOMICAL_UNIT / length(&sun);
+    let sun_parallax = (EARTH_RADIUS / length(&sun)).asin();
+    let moon_parallax = (EARTH_RADIUS / length(&moon)).asin();
+    let moon_radius = (MOON_RADIUS / length(&moon)).asin();
+
+    let umbra =
+        SHADOW_ENLARGEMENT * (SHADOW_EARTH_RADIUS * moon_parallax + sun_parallax - sun_radius);
+    let penumbra =
+        SHADOW_ENLARGEMENT * (SHADOW_EARTH_RADIUS * moon_parallax + sun_parallax + sun_radius);
+
+    Circumstances {
+        separation: angle_between(&shadow, &moon),
+        phases: vec![
+            (PhaseKind::Penumbral, penumbra + moon_radius),
+            (PhaseKind::Partial, umbra + moon_radius),
+            (PhaseKind::Total, umbra - moon_radius),
+        ],
+    }
+}
+
+/// Time of the greatest eclipse and the start and end of each phase
+struct Contacts {
+    maximum: NaiveDateTime,
+    phases: Vec<(PhaseKind, NaiveDateTime, NaiveDateTime)>,
+}
+
+/// Bisection for the contact between a time `outside` and a time `inside` of a phase,
+/// down to one second
+fn contact<F>(depth: &F, mut outside: NaiveDateTime, mut inside: NaiveDateTime) -> NaiveDateTime
+where
+    F: Fn(&NaiveDateTime) -> f64,
+{
+    while (inside - outside).num_seconds().abs() > 1 {
+        let middle = outside + (inside - outside) / 2;
+
+        if depth(&middle) > 0. {
+            inside = middle;
+        } else {
+            outside = middle;
+        }
+    }
+
+    inside
+}
+
+/// Find the eclipses with the given circumstances for `days` consecutive days starting at
+/// `time`
+fn find_contacts<F>(circumstances: F, time: &NaiveDateTime, days: u32) -> Vec<Contacts>
+where
+    F: Fn(&NaiveDateTime) -> Circumstances,
+{
+    let step = Duration::try_hours(SCAN_STEP_HOURS).expect("constant value");
+    let window = Duration::try_hours(CONTACT_WINDOW_HOURS).expect("constant value");
+    let separation_at = |time: &NaiveDateTime| circumstances(time).separation;
+
+    let samples = i64::from(days) * 24 / SCAN_STEP_HOURS;
+    let separations: Vec<(NaiveDateTime, f64)> = (0..=samples)
+        .map(|i| {
+            let time = *time + step * i as i32;
+            (time, separation_at(&time))
+        })
+        .collect();
+
+    separations
+        .windows(3)
+        .filter(|window| {
+            window[1].1 < SCAN_MAX_SEPARATION
+                && window[1].1 <= window[0].1
+                && window[1].1 < window[2].1
+        })
+        .filter_map(|samples| {
+            let maximum = find_minimum(&separation_at, samples[0].0, samples[2].0);
+            let at_maximum = circumstances(&maximum);
+
+            let phases: Vec<_> = at_maximum
+                .phases
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, limit))| at_maximum.separation < *limit)
+                .map(|(i, (kind, _))| {
+                    let depth = |time: &NaiveDateTime| {
+                        let Circumstances { separation, phases } = circumstances(time);
+                        phases[i].1 - separation
+                    };
+
+                    (
+                        *kind,
+                        contact(&depth, maximum - window, maximum),
+                        contact(&depth, maximum + window, maximum),
+                    )
+                })
+                .collect();
+
+            (!phases.is_empty()).then_some(Contacts { maximum, phases })
+        })
+        .collect()
+}
+
+fn with_visibility<O>(
+    object: &O,
+    kind: EclipseKind,
+    Contacts { maximum, phases }: Contacts,
+    location: &Location,
+    horizon: &Horizon,
+) -> Eclipse
+where
+    O: SkyObject,
+{
+    let phases = phases
+        .into_iter()
+        .map(|(phase, start, end)| EclipsePhase {
+            kind: phase,
+            start: start.and_utc(),
+            end: end.and_utc(),
+            visible: calculate_visibility_intervals(object, &start, &end, location, horizon),
+        })
+        .collect();
+
+    Eclipse {
+        kind,
+        maximum: maximum.and_utc(),
+        phases,
+    }
+}
+
+/// Find the solar and lunar eclipses at the location for `days` consecutive days starting at
+/// `time`, in chronological order, and when their phases can be seen above the horizon.
+///
+/// Solar eclipses are the local circumstances at the location, a solar eclipse which is only
+/// seen elsewhere on earth is not found.
+pub fn find_eclipses(
+    time: &NaiveDateTime,
+    days: u32,
+    location: &Location,
+    horizon: &Horizon,
+) -> Vec<Eclipse> {
+    let solar = find_contacts(|time| solar_circumstances(time, location), time, days)
+        .into_iter()
+        .map(|contacts| {
+            with_visibility(&Vsop87Sun, EclipseKind::Solar, contacts, location, horizon)
+        });
+    let lunar = find_contacts(lunar_circumstances, time, days)
+        .into_iter()
+        .map(|contacts| {
+            with_visibility(&MeeusMoon, EclipseKind::Lunar, contacts, location, horizon)
+        });
+
+    let mut eclipses: Vec<Eclipse> = solar.chain(lunar).collect();
+    eclipses.sort_by_key(|eclipse| eclipse.maximum);
+    eclipses
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[test]
+    fn angle_between_vectors() {
+        let a = rectangular(0., 0., 1.);
+        let b = rectangular(FRAC_PI_2, 0., 2.);
+        let c = rectangular(1., 0.5, 3.);
+
+        assert!((angle_between(&a, &b) - FRAC_PI_2).abs() < 1e-12);
+        assert!(angle_between(&c, &c).abs() < 1e-7);
+        assert!((angle_between(&a, &a.map(|x| -x)) - PI).abs() < 1e-12);
+    }
+}
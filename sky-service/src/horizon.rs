@@ -1,70 +1,597 @@
-use std::f64::consts::PI;
+use std::f64::consts::{PI, TAU};
 
-use anyhow::{anyhow, Error};
-use bytes::Bytes;
+use anyhow::{anyhow, ensure, Error};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-use crate::angle::AngleExtensions;
+use crate::{angle::Radians, location::Location};
 
+pub mod analysis;
+pub mod formats;
+
+/// Number of samples of the legacy format, which has no header
 pub const HORIZON_SAMPLES: usize = 1024;
+/// Azimuth between two samples of the legacy format (in radians)
 pub const HORIZON_ANGLE: f64 = 2. * PI / (HORIZON_SAMPLES as f64);
 
+/// Farthest distance between the observer a horizon was computed for and the location it is
+/// used for (in metres)
+pub const MAX_OBSERVER_DISTANCE: f64 = 100.;
+
 const BYTES_IN_F64: usize = 8;
 
-#[derive(Debug)]
+/// Start of every horizon in the versioned format
+const MAGIC: &[u8; 4] = b"HRZN";
+/// Current version of the binary format
+const FORMAT_VERSION: u16 = 1;
+/// The header is followed by the azimuths of the samples before their altitudes
+const FLAG_AZIMUTHS: u16 = 1;
+/// The header carries the metadata of the horizon
+const FLAG_METADATA: u16 = 2;
+/// Magic, version, flags and number of samples
+const HEADER_LENGTH: usize = 4 + 2 + 2 + 4;
+/// Latitude, longitude and elevation of the observer, creation time and length of the source
+const METADATA_LENGTH: usize = 3 * BYTES_IN_F64 + 8 + 4;
+
+/// Unit of the azimuths and altitudes of an encoded horizon
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HorizonUnit {
+    #[default]
+    Radians,
+    Degrees,
+}
+
+impl TryFrom<u8> for HorizonUnit {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(HorizonUnit::Radians),
+            1 => Ok(HorizonUnit::Degrees),
+            _ => Err(anyhow!("Unknown horizon unit {value}")),
+        }
+    }
+}
+
+impl From<HorizonUnit> for u8 {
+    fn from(unit: HorizonUnit) -> Self {
+        match unit {
+            HorizonUnit::Radians => 0,
+            HorizonUnit::Degrees => 1,
+        }
+    }
+}
+
+/// Where a horizon was computed for and where its terrain came from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HorizonMetadata {
+    /// Latitude of the observer
+    pub lat: f64,
+    /// Longitude of the observer
+    pub lon: f64,
+    /// Elevation of the observer above sea level (in metres)
+    pub elevation: f64,
+    /// Elevation model the terrain was taken from, e.g. "SRTM 1 arc-second"
+    pub source: String,
+    /// Time the horizon was computed, in whole seconds
+    pub created: DateTime<Utc>,
+}
+
+/// Reasons for not using a horizon for a location
+#[derive(Debug, Error)]
+pub enum HorizonMismatch {
+    #[error("the horizon was computed {distance:.0} m away from the location")]
+    Location { distance: f64 },
+    #[error("the horizon was computed at {created}, before {oldest}")]
+    Stale {
+        created: DateTime<Utc>,
+        oldest: DateTime<Utc>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Horizon {
-    altitudes: [f64; HORIZON_SAMPLES],
+    /// Azimuths of the samples (in radians) in increasing order within one turn, `None` for
+    /// equally spaced samples starting at north
+    azimuths: Option<Vec<f64>>,
+    altitudes: Vec<f64>,
+    metadata: Option<HorizonMetadata>,
 }
 
 impl Horizon {
-    pub fn new(altitudes: [f64; HORIZON_SAMPLES]) -> Self {
-        Self { altitudes }
+    /// A horizon from its altitudes (in radians) at equally spaced azimuths, starting at north
+    /// and going towards east
+    ///
+    /// # Panics
+    ///
+    /// If there are no altitudes
+    pub fn new(altitudes: impl Into<Vec<f64>>) -> Self {
+        let altitudes = altitudes.into();
+        assert!(!altitudes.is_empty(), "a horizon needs at least one sample");
+
+        Self {
+            azimuths: None,
+            altitudes,
+            metadata: None,
+        }
     }
 
-    pub fn altitude_at(&self, pos: f64) -> f64 {
-        let pos = pos.normalize_radians();
+    /// A horizon from its altitudes (in radians) at the given azimuths (in radians from north
+    /// towards east), which have to be increasing within one turn
+    pub fn with_azimuths(azimuths: Vec<f64>, altitudes: Vec<f64>) -> Result<Self, Error> {
+        ensure!(!altitudes.is_empty(), "A horizon needs at least one sample");
+        ensure!(
+            azimuths.len() == altitudes.len(),
+            "Expected an azimuth for each of the {} altitudes, had {}",
+            altitudes.len(),
+            azimuths.len()
+        );
+        ensure!(
+            azimuths.iter().all(|azimuth| (0. ..TAU).contains(azimuth)),
+            "Azimuths have to be within [0, 2π)"
+        );
+        ensure!(
+            azimuths.windows(2).all(|pair| pair[0] < pair[1]),
+            "Azimuths have to be strictly increasing"
+        );
 
-        let left = (pos / HORIZON_ANGLE).floor() as usize;
-        let right = if left < HORIZON_SAMPLES - 1 {
-            left + 1
-        } else {
-            0
+        Ok(Self {
+            azimuths: Some(azimuths),
+            altitudes,
+            metadata: None,
+        })
+    }
+
+    /// The horizon described by the metadata
+    pub fn with_metadata(self, metadata: HorizonMetadata) -> Self {
+        Self {
+            metadata: Some(metadata),
+            ..self
+        }
+    }
+
+    /// Where the horizon was computed for and where its terrain came from, unknown for
+    /// horizons in the legacy format
+    pub fn metadata(&self) -> Option<&HorizonMetadata> {
+        self.metadata.as_ref()
+    }
+
+    /// Check that the horizon was computed for the location and not before `oldest`.
+    /// Horizons without metadata cannot be checked and are accepted.
+    pub fn check(
+        &self,
+        location: &Location,
+        oldest: Option<DateTime<Utc>>,
+    ) -> Result<(), HorizonMismatch> {
+        let Some(metadata) = &self.metadata else {
+            return Ok(());
         };
 
+        let distance = location.distance(metadata.lat, metadata.lon);
+        if distance > MAX_OBSERVER_DISTANCE {
+            return Err(HorizonMismatch::Location { distance });
+        }
+
+        match oldest {
+            Some(oldest) if metadata.created < oldest => Err(HorizonMismatch::Stale {
+                created: metadata.created,
+                oldest,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Number of samples of the horizon
+    pub fn samples(&self) -> usize {
+        self.altitudes.len()
+    }
+
+    /// The horizon at `samples` equally spaced azimuths starting at north, linearly
+    /// interpolated between the samples of this one
+    ///
+    /// # Panics
+    ///
+    /// If `samples` is zero
+    pub fn resample(&self, samples: usize) -> Horizon {
+        let altitudes: Vec<f64> = (0..samples)
+            .map(|i| self.altitude_at(Radians(TAU * i as f64 / samples as f64)).0)
+            .collect();
+
+        Horizon {
+            metadata: self.metadata.clone(),
+            ..Horizon::new(altitudes)
+        }
+    }
+
+    /// Azimuth of the sample `i` (in radians)
+    fn azimuth(&self, i: usize) -> f64 {
+        match &self.azimuths {
+            Some(azimuths) => azimuths[i],
+            None => i as f64 * TAU / self.samples() as f64,
+        }
+    }
+
+    /// Azimuth from the sample `i` to the next one, wrapping around at north
+    fn width(&self, i: usize) -> f64 {
+        if self.samples() == 1 {
+            return TAU;
+        }
+
+        let next = (i + 1) % self.samples();
+        (self.azimuth(next) - self.azimuth(i)).rem_euclid(TAU)
+    }
+
+    /// The altitude at the azimuth, linearly interpolated between the two surrounding samples
+    pub fn altitude_at(&self, azimuth: Radians) -> Radians {
+        let Radians(pos) = azimuth.normalize();
+        let samples = self.samples();
+
+        let left = match &self.azimuths {
+            Some(azimuths) => {
+                // Before the first sample, interpolate from the last one across north
+                let after = azimuths.partition_point(|azimuth| *azimuth <= pos);
+                (after + samples - 1) % samples
+            }
+            None => ((pos * samples as f64 / TAU).floor() as usize).min(samples - 1),
+        };
+        let right = (left + 1) % samples;
+
         let left_height = self.altitudes[left];
         let right_height = self.altitudes[right];
 
-        let offset = pos - left as f64 * HORIZON_ANGLE;
+        let width = self.width(left);
+        let offset = (pos - self.azimuth(left)).rem_euclid(TAU);
+
+        Radians(left_height + offset * (right_height - left_height) / width)
+    }
+
+    pub fn lowest(&self) -> Radians {
+        Radians(self.altitudes.iter().copied().fold(f64::INFINITY, f64::min))
+    }
 
-        left_height + offset * (right_height - left_height) / HORIZON_ANGLE
+    pub fn highest(&self) -> Radians {
+        Radians(
+            self.altitudes
+                .iter()
+                .copied()
+                .fold(f64::NEG_INFINITY, f64::max),
+        )
     }
+
+    /// Largest change of the altitude per change of the azimuth between two samples
+    pub fn max_slope(&self) -> f64 {
+        let samples = self.samples();
+        (0..samples)
+            .map(|i| {
+                let next = self.altitudes[(i + 1) % samples];
+                (next - self.altitudes[i]).abs() / self.width(i)
+            })
+            .fold(0., f64::max)
+    }
+}
+
+fn read_f64s(bytes: &mut Bytes, count: usize) -> Vec<f64> {
+    (0..count).map(|_| bytes.get_f64_le()).collect()
+}
+
+/// Split off the next `length` bytes of the input
+fn take(value: &mut Bytes, length: usize, what: &str) -> Result<Bytes, Error> {
+    ensure!(
+        value.len() >= length,
+        "Expected {length} bytes for the {what}, had {}",
+        value.len()
+    );
+
+    Ok(value.split_to(length))
+}
+
+fn read_metadata(value: &mut Bytes) -> Result<HorizonMetadata, Error> {
+    let mut fixed = take(value, METADATA_LENGTH, "metadata")?;
+    let lat = fixed.get_f64_le();
+    let lon = fixed.get_f64_le();
+    let elevation = fixed.get_f64_le();
+    let created = fixed.get_i64_le();
+    let created =
+        DateTime::from_timestamp(created, 0).ok_or(anyhow!("Invalid creation time {created}"))?;
+    let source_length = fixed.get_u32_le() as usize;
+    let source = take(value, source_length, "source")?;
+
+    Ok(HorizonMetadata {
+        lat,
+        lon,
+        elevation,
+        source: String::from_utf8(source.to_vec())?,
+        created,
+    })
 }
 
 impl TryFrom<Bytes> for Horizon {
     type Error = Error;
 
-    fn try_from(value: Bytes) -> Result<Self, Self::Error> {
-        if value.len() != HORIZON_SAMPLES * BYTES_IN_F64 {
-            return Err(anyhow!(
-                "Expected input to have {} bytes, had {}",
-                HORIZON_SAMPLES * BYTES_IN_F64,
-                value.len()
-            ));
-        }
-
-        let altitudes: Vec<f64> = value
-            .chunks(BYTES_IN_F64)
-            .map(|bytes| {
-                let bytes: &[u8; BYTES_IN_F64] = bytes
-                    .try_into()
-                    .expect("Chunk of input did not have correct size. Should never happen");
-                f64::from_le_bytes(*bytes)
-            })
-            .collect();
+    /// Decode a horizon in the versioned format, or in the legacy format of exactly
+    /// [`HORIZON_SAMPLES`] little endian altitudes without a header
+    fn try_from(mut value: Bytes) -> Result<Self, Self::Error> {
+        if !value.starts_with(MAGIC) {
+            if value.len() != HORIZON_SAMPLES * BYTES_IN_F64 {
+                return Err(anyhow!(
+                    "Expected input to have {} bytes, had {}",
+                    HORIZON_SAMPLES * BYTES_IN_F64,
+                    value.len()
+                ));
+            }
+
+            return Ok(Horizon::new(read_f64s(&mut value, HORIZON_SAMPLES)));
+        }
+
+        let mut header = take(&mut value, HEADER_LENGTH, "header")?;
+        header.advance(MAGIC.len());
+        let version = header.get_u16_le();
+        ensure!(
+            version == FORMAT_VERSION,
+            "Unsupported horizon format version {version}"
+        );
+        let flags = header.get_u16_le();
+        let samples = header.get_u32_le() as usize;
+        ensure!(samples > 0, "A horizon needs at least one sample");
+
+        let unit = HorizonUnit::try_from(take(&mut value, 1, "unit")?.get_u8())?;
+        let metadata = (flags & FLAG_METADATA != 0)
+            .then(|| read_metadata(&mut value))
+            .transpose()?;
+
+        let has_azimuths = flags & FLAG_AZIMUTHS != 0;
+        let expected = samples * BYTES_IN_F64 * if has_azimuths { 2 } else { 1 };
+        ensure!(
+            value.len() == expected,
+            "Expected {expected} bytes for {samples} samples, had {}",
+            value.len()
+        );
+
+        let mut read_angles = |count| {
+            let angles = read_f64s(&mut value, count);
+            match unit {
+                HorizonUnit::Radians => angles,
+                HorizonUnit::Degrees => angles.into_iter().map(f64::to_radians).collect(),
+            }
+        };
+        let horizon = if has_azimuths {
+            let azimuths = read_angles(samples);
+            let altitudes = read_angles(samples);
+            Horizon::with_azimuths(azimuths, altitudes)?
+        } else {
+            Horizon::new(read_angles(samples))
+        };
+
+        Ok(Horizon {
+            metadata,
+            ..horizon
+        })
+    }
+}
+
+impl Horizon {
+    /// Encode the horizon in the current version of the versioned format, with the azimuths
+    /// and altitudes in the unit
+    pub fn encode(&self, unit: HorizonUnit) -> Bytes {
+        let values = self.samples() * if self.azimuths.is_some() { 2 } else { 1 };
+        let metadata_length = self
+            .metadata
+            .as_ref()
+            .map_or(0, |metadata| METADATA_LENGTH + metadata.source.len());
+        let mut bytes =
+            BytesMut::with_capacity(HEADER_LENGTH + 1 + metadata_length + values * BYTES_IN_F64);
+
+        let mut flags = 0;
+        if self.azimuths.is_some() {
+            flags |= FLAG_AZIMUTHS;
+        }
+        if self.metadata.is_some() {
+            flags |= FLAG_METADATA;
+        }
+
+        bytes.put_slice(MAGIC);
+        bytes.put_u16_le(FORMAT_VERSION);
+        bytes.put_u16_le(flags);
+        bytes.put_u32_le(self.samples() as u32);
+        bytes.put_u8(unit.into());
+
+        if let Some(metadata) = &self.metadata {
+            bytes.put_f64_le(metadata.lat);
+            bytes.put_f64_le(metadata.lon);
+            bytes.put_f64_le(metadata.elevation);
+            bytes.put_i64_le(metadata.created.timestamp());
+            bytes.put_u32_le(metadata.source.len() as u32);
+            bytes.put_slice(metadata.source.as_bytes());
+        }
+
+        let to_unit = |angle: f64| match unit {
+            HorizonUnit::Radians => angle,
+            HorizonUnit::Degrees => angle.to_degrees(),
+        };
+        for azimuth in self.azimuths.iter().flatten() {
+            bytes.put_f64_le(to_unit(*azimuth));
+        }
+        for altitude in &self.altitudes {
+            bytes.put_f64_le(to_unit(*altitude));
+        }
+
+        bytes.freeze()
+    }
+
+    /// Encode the horizon in the legacy format, the altitudes (in radians) at the
+    /// [`HORIZON_SAMPLES`] azimuths of the format without a header. The metadata is lost.
+    pub fn encode_legacy(&self) -> Bytes {
+        let mut bytes = BytesMut::with_capacity(HORIZON_SAMPLES * BYTES_IN_F64);
+        for i in 0..HORIZON_SAMPLES {
+            let altitude = self.altitude_at(Radians(i as f64 * HORIZON_ANGLE));
+            bytes.put_f64_le(altitude.0);
+        }
+
+        bytes.freeze()
+    }
+}
+
+impl From<&Horizon> for Bytes {
+    /// Encode the horizon in the versioned format, in radians
+    fn from(horizon: &Horizon) -> Self {
+        horizon.encode(HorizonUnit::Radians)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::angle::Degrees;
+    use crate::util::assert_precisely_eq;
+
+    #[test]
+    fn interpolate_uniform() {
+        // A sample every 90°
+        let horizon = Horizon::new([0., 0.4, 0.2, 0.]);
 
-        let altitudes: [f64; HORIZON_SAMPLES] = altitudes
-            .try_into()
-            .expect("Altitudes result array did not have correct size. Should never happen");
+        assert_precisely_eq(horizon.altitude_at(Radians(0.)).0, 0.);
+        assert_precisely_eq(horizon.altitude_at(Degrees(45.).to_radians()).0, 0.2);
+        assert_precisely_eq(horizon.altitude_at(Degrees(90.).to_radians()).0, 0.4);
+        assert_precisely_eq(horizon.altitude_at(Degrees(135.).to_radians()).0, 0.3);
+        assert_precisely_eq(horizon.altitude_at(Degrees(-90.).to_radians()).0, 0.);
+        assert!((horizon.max_slope() - 0.4 / (PI / 2.)).abs() < 1e-12);
+
+        let single = Horizon::new([0.1]);
+        assert_precisely_eq(single.altitude_at(Degrees(123.).to_radians()).0, 0.1);
+    }
+
+    #[test]
+    fn interpolate_non_uniform() {
+        let azimuths = [10., 100., 250.]
+            .map(|azimuth| Degrees(azimuth).to_radians().0)
+            .to_vec();
+        let horizon = Horizon::with_azimuths(azimuths, vec![0.1, 0.4, 0.2]).unwrap();
+
+        assert_precisely_eq(horizon.altitude_at(Degrees(10.).to_radians()).0, 0.1);
+        assert_precisely_eq(horizon.altitude_at(Degrees(55.).to_radians()).0, 0.25);
+        assert_precisely_eq(horizon.altitude_at(Degrees(175.).to_radians()).0, 0.3);
+
+        // Across north, from 250° over 120° to 10°
+        assert_precisely_eq(horizon.altitude_at(Degrees(310.).to_radians()).0, 0.15);
+        assert_precisely_eq(
+            horizon.altitude_at(Degrees(0.).to_radians()).0,
+            0.1 + 0.1 / 12.,
+        );
+        assert!((horizon.max_slope() - 0.3 / Degrees(90.).to_radians().0).abs() < 1e-12);
+
+        assert!(Horizon::with_azimuths(vec![0.2, 0.1], vec![0., 0.]).is_err());
+        assert!(Horizon::with_azimuths(vec![0.1], vec![0., 0.]).is_err());
+        assert!(Horizon::with_azimuths(vec![0.1, 7.], vec![0., 0.]).is_err());
+    }
+
+    #[test]
+    fn decode_legacy() {
+        let mut bytes = BytesMut::new();
+        for i in 0..HORIZON_SAMPLES {
+            bytes.put_f64_le(i as f64 / 1000.);
+        }
+
+        let horizon = Horizon::try_from(bytes.freeze()).unwrap();
+        assert_eq!(horizon.samples(), HORIZON_SAMPLES);
+        assert_precisely_eq(horizon.altitude_at(Radians(HORIZON_ANGLE * 2.5)).0, 0.0025);
+
+        assert!(Horizon::try_from(Bytes::from_static(&[0; 8])).is_err());
+    }
+
+    #[test]
+    fn encode_legacy() {
+        let horizon = Horizon::new(vec![0.1, 0.3]).with_metadata(metadata());
+        let bytes = horizon.encode_legacy();
+        assert_eq!(bytes.len(), HORIZON_SAMPLES * BYTES_IN_F64);
+
+        let decoded = Horizon::try_from(bytes).unwrap();
+        assert_eq!(decoded.samples(), HORIZON_SAMPLES);
+        assert_eq!(decoded.metadata(), None);
+        assert_precisely_eq(decoded.altitude_at(Radians(PI / 2.)).0, 0.2);
+        assert_precisely_eq(decoded.altitude_at(Radians(PI)).0, 0.3);
+    }
+
+    #[test]
+    fn encode_and_decode() {
+        let uniform = Horizon::new(vec![0.1, 0.2, 0.3]);
+        let non_uniform = Horizon::with_azimuths(vec![0., 1., 4.], vec![0.1, 0.2, 0.3]).unwrap();
+
+        for horizon in [uniform, non_uniform] {
+            let bytes = Bytes::from(&horizon);
+            assert_eq!(Horizon::try_from(bytes.clone()).unwrap(), horizon);
+
+            // Truncated input
+            assert!(Horizon::try_from(bytes.slice(..bytes.len() - 1)).is_err());
+        }
+
+        let mut unknown = BytesMut::from(&Bytes::from(&Horizon::new([0.]))[..]);
+        unknown[4] = 99;
+        assert!(Horizon::try_from(unknown.freeze()).is_err());
+    }
+
+    fn metadata() -> HorizonMetadata {
+        HorizonMetadata {
+            lat: 47.,
+            lon: 11.,
+            elevation: 1200.,
+            source: "SRTM 1 arc-second".to_string(),
+            created: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn encode_and_decode_metadata() {
+        let horizon = Horizon::with_azimuths(vec![0., 1., 4.], vec![0.1, 0.2, 0.3])
+            .unwrap()
+            .with_metadata(metadata());
+
+        let decoded = Horizon::try_from(horizon.encode(HorizonUnit::Radians)).unwrap();
+        assert_eq!(decoded, horizon);
+
+        let decoded = Horizon::try_from(horizon.encode(HorizonUnit::Degrees)).unwrap();
+        assert_eq!(decoded.metadata(), Some(&metadata()));
+        for azimuth in [0., 0.5, 2., 5.] {
+            let azimuth = Radians(azimuth);
+            assert_precisely_eq(
+                decoded.altitude_at(azimuth).0,
+                horizon.altitude_at(azimuth).0,
+            );
+        }
+    }
+
+    #[test]
+    fn check_location_and_age() {
+        let horizon = Horizon::new([0.]).with_metadata(metadata());
+        let location = Location {
+            lat: 47.,
+            lon: 11.,
+            ..Default::default()
+        };
+        let created = metadata().created;
+
+        assert!(horizon.check(&location, None).is_ok());
+        assert!(horizon.check(&location, Some(created)).is_ok());
+        assert!(matches!(
+            horizon.check(
+                &location,
+                Some(created + chrono::Duration::try_days(1).unwrap())
+            ),
+            Err(HorizonMismatch::Stale { .. })
+        ));
+
+        // About 1.1 km to the north
+        let elsewhere = Location {
+            lat: 47.01,
+            ..location
+        };
+        assert!(matches!(
+            horizon.check(&elsewhere, None),
+            Err(HorizonMismatch::Location { distance }) if (distance - 1112.).abs() < 1.
+        ));
 
-        Ok(Horizon { altitudes })
+        // Without metadata there is nothing to check
+        assert!(Horizon::new([0.]).check(&elsewhere, None).is_ok());
     }
 }
@@ -0,0 +1,245 @@
+//! Features of the terrain around a spot, e.g. to find benches with an open view to the west
+
+use std::f64::consts::TAU;
+
+use serde::{Deserialize, Serialize};
+
+use super::Horizon;
+use crate::{
+    alignment::AzimuthRange,
+    angle::{Degrees, Radians},
+};
+
+/// Notches shallower than this are left out of a summary
+pub const MIN_NOTCH_PROMINENCE: Degrees = Degrees(1.);
+/// Where the horizon is lower than this, a summary counts the view as open
+pub const OPEN_VIEW_ALTITUDE: Degrees = Degrees(1.);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HorizonPoint {
+    pub azimuth: Radians,
+    pub altitude: Radians,
+}
+
+/// A local minimum of the horizon, e.g. a gap between two mountains
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Notch {
+    pub azimuth: Radians,
+    pub altitude: Radians,
+    /// How far the horizon rises on either side before it gets lower than the notch,
+    /// the lower of the two
+    pub prominence: Radians,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HorizonSummary {
+    pub lowest: HorizonPoint,
+    pub highest: HorizonPoint,
+    /// Notches at least [`MIN_NOTCH_PROMINENCE`] deep
+    pub notches: Vec<Notch>,
+    /// Share of the sky hemisphere hidden by the terrain, between 0 and 1
+    pub obstruction: f64,
+    /// Azimuth ranges in which the horizon is lower than [`OPEN_VIEW_ALTITUDE`]
+    pub open_ranges: Vec<AzimuthRange>,
+}
+
+impl Horizon {
+    fn point(&self, i: usize) -> HorizonPoint {
+        HorizonPoint {
+            azimuth: Radians(self.azimuth(i)),
+            altitude: Radians(self.altitudes[i]),
+        }
+    }
+
+    pub fn lowest_point(&self) -> HorizonPoint {
+        let lowest = (0..self.samples())
+            .min_by(|a, b| self.altitudes[*a].total_cmp(&self.altitudes[*b]))
+            .expect("a horizon has at least one sample");
+
+        self.point(lowest)
+    }
+
+    pub fn highest_point(&self) -> HorizonPoint {
+        let highest = (0..self.samples())
+            .max_by(|a, b| self.altitudes[*a].total_cmp(&self.altitudes[*b]))
+            .expect("a horizon has at least one sample");
+
+        self.point(highest)
+    }
+
+    /// The local minima of the horizon at least `min_prominence` deep, by azimuth. The lowest
+    /// point of the horizon is as deep as the highest one is high.
+    pub fn notches(&self, min_prominence: Radians) -> Vec<Notch> {
+        let samples = self.samples();
+        let at = |i: usize| self.altitudes[i % samples];
+
+        // The first sample of a flat bottom is the notch
+        (0..samples)
+            .filter(|&i| at(i + samples - 1) > at(i) && at(i + 1) >= at(i))
+            .filter_map(|i| {
+                let altitude = at(i);
+                let highest_before_lower = |step: usize| {
+                    (1..samples)
+                        .map(|k| at(i + k * step))
+                        .take_while(|other| *other >= altitude)
+                        .fold(altitude, f64::max)
+                };
+                // Stepping by one less than the number of samples goes backwards
+                let prominence =
+                    highest_before_lower(1).min(highest_before_lower(samples - 1)) - altitude;
+
+                (prominence >= min_prominence.0).then(|| Notch {
+                    azimuth: Radians(self.azimuth(i)),
+                    altitude: Radians(altitude),
+                    prominence: Radians(prominence),
+                })
+            })
+            .collect()
+    }
+
+    /// Share of the solid angle of the sky hemisphere below the horizon, between 0 and 1.
+    /// Terrain below the astronomical horizon hides nothing.
+    pub fn obstruction(&self) -> f64 {
+        let samples = self.samples();
+        // Solid angle from the astronomical horizon up to the altitude, per azimuth
+        let hidden = |i: usize| self.altitudes[i % samples].max(0.).sin();
+
+        let solid_angle: f64 = (0..samples)
+            .map(|i| self.width(i) * (hidden(i) + hidden(i + 1)) / 2.)
+            .sum();
+        solid_angle / TAU
+    }
+
+    /// The azimuth ranges in which the horizon is lower than `threshold`, clockwise from
+    /// the first one after north
+    pub fn ranges_below(&self, threshold: Radians) -> Vec<AzimuthRange> {
+        let samples = self.samples();
+        let height = |i: usize| self.altitudes[i % samples] - threshold.0;
+        let below = |i: usize| height(i) < 0.;
+
+        // Azimuths at which the horizon drops below the threshold or rises above it again
+        let changes: Vec<(f64, bool)> = (0..samples)
+            .filter(|&i| below(i) != below(i + 1))
+            .map(|i| {
+                let share = height(i) / (height(i) - height(i + 1));
+                let azimuth = (self.azimuth(i) + share * self.width(i)).rem_euclid(TAU);
+                (azimuth, below(i + 1))
+            })
+            .collect();
+
+        let Some(first_drop) = changes.iter().position(|(_, drops)| *drops) else {
+            return if below(0) {
+                vec![AzimuthRange {
+                    start: Radians(0.),
+                    end: Radians(TAU),
+                }]
+            } else {
+                vec![]
+            };
+        };
+
+        // Drops and rises alternate around the horizon
+        let changes: Vec<f64> = changes[first_drop..]
+            .iter()
+            .chain(&changes[..first_drop])
+            .map(|(azimuth, _)| *azimuth)
+            .collect();
+        changes
+            .chunks(2)
+            .map(|pair| AzimuthRange {
+                start: Radians(pair[0]),
+                end: Radians(pair[1]),
+            })
+            .collect()
+    }
+
+    pub fn summary(&self) -> HorizonSummary {
+        HorizonSummary {
+            lowest: self.lowest_point(),
+            highest: self.highest_point(),
+            notches: self.notches(MIN_NOTCH_PROMINENCE.to_radians()),
+            obstruction: self.obstruction(),
+            open_ranges: self.ranges_below(OPEN_VIEW_ALTITUDE.to_radians()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::assert_degrees_eq;
+
+    /// A horizon of equally spaced altitudes in degrees
+    fn horizon(altitudes: &[f64]) -> Horizon {
+        let altitudes: Vec<f64> = altitudes
+            .iter()
+            .map(|altitude| altitude.to_radians())
+            .collect();
+        Horizon::new(altitudes)
+    }
+
+    #[test]
+    fn lowest_and_highest_points() {
+        let horizon = horizon(&[5., 5., 5., 2., 5., 9., 5., 5.]);
+
+        let lowest = horizon.lowest_point();
+        assert_degrees_eq(lowest.azimuth, 135., 1e-9);
+        assert_degrees_eq(lowest.altitude, 2., 1e-9);
+
+        let highest = horizon.highest_point();
+        assert_degrees_eq(highest.azimuth, 225., 1e-9);
+        assert_degrees_eq(highest.altitude, 9., 1e-9);
+    }
+
+    #[test]
+    fn notches_by_prominence() {
+        let horizon = horizon(&[10., 4., 8., 6., 9., 10., 10., 10.]);
+
+        let notches = horizon.notches(Radians(0.));
+        assert_eq!(notches.len(), 2);
+        // The lowest point is bounded by the highest one
+        assert_degrees_eq(notches[0].azimuth, 45., 1e-9);
+        assert_degrees_eq(notches[0].prominence, 6., 1e-9);
+        // The other one by the ridge between them
+        assert_degrees_eq(notches[1].azimuth, 135., 1e-9);
+        assert_degrees_eq(notches[1].altitude, 6., 1e-9);
+        assert_degrees_eq(notches[1].prominence, 2., 1e-9);
+
+        let deep = horizon.notches(Degrees(3.).to_radians());
+        assert_eq!(deep, notches[..1]);
+
+        // A flat bottom is one notch, a flat horizon has none
+        let flat_bottom = self::horizon(&[5., 1., 1., 1., 5., 5.]);
+        assert_eq!(flat_bottom.notches(Radians(0.)).len(), 1);
+        assert!(self::horizon(&[3.; 8]).notches(Radians(0.)).is_empty());
+    }
+
+    #[test]
+    fn obstruction_of_the_hemisphere() {
+        assert!((horizon(&[30.; 16]).obstruction() - 0.5).abs() < 1e-12);
+        assert_eq!(horizon(&[-1.; 16]).obstruction(), 0.);
+        assert!((horizon(&[90.]).obstruction() - 1.).abs() < 1e-12);
+
+        // Half the horizon at 30°, ramps in between
+        let half = horizon(&[30., 30., 0., 0.]);
+        assert!((half.obstruction() - 0.25).abs() < 1e-12);
+    }
+
+    #[test]
+    fn ranges_below_threshold() {
+        let horizon = horizon(&[0., 2., 0., 2.]);
+        let ranges = horizon.ranges_below(Degrees(1.).to_radians());
+
+        assert_eq!(ranges.len(), 2);
+        assert_degrees_eq(ranges[0].start, 135., 1e-9);
+        assert_degrees_eq(ranges[0].end, 225., 1e-9);
+        // Across north
+        assert_degrees_eq(ranges[1].start, 315., 1e-9);
+        assert_degrees_eq(ranges[1].end, 45., 1e-9);
+
+        assert!(horizon.ranges_below(Radians(0.)).is_empty());
+        let everywhere = horizon.ranges_below(Degrees(3.).to_radians());
+        assert_eq!(everywhere.len(), 1);
+        assert!(everywhere[0].contains(Degrees(200.).to_radians()));
+    }
+}
@@ -0,0 +1,278 @@
+//! Horizon profiles exchanged with other tools. Imported horizons are resampled to
+//! [`HORIZON_SAMPLES`] equally spaced azimuths.
+
+use std::{f64::consts::TAU, fmt::Write, str::FromStr};
+
+use anyhow::{anyhow, bail, ensure, Error};
+
+use super::{Horizon, HORIZON_SAMPLES};
+
+/// Azimuths closer than this (in radians) are considered the same, e.g. 0° and 360°
+const SAME_AZIMUTH: f64 = 1e-9;
+
+/// Units of the `polygonal_horizon_list_mode` of a Stellarium landscape
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StellariumMode {
+    /// Azimuths and altitudes in degrees
+    #[default]
+    AzDegAltDeg,
+    /// Azimuths and zenith distances in degrees
+    AzDegZdDeg,
+    AzRadAltRad,
+    AzRadZdRad,
+    /// Azimuths and altitudes in gradians, 400 to a full turn
+    AzGradAltGrad,
+    AzGradZdGrad,
+}
+
+impl StellariumMode {
+    /// Azimuth and altitude in radians from the two values of a line
+    fn to_radians(self, first: f64, second: f64) -> (f64, f64) {
+        let (per_turn, zenith_distance) = match self {
+            StellariumMode::AzDegAltDeg => (360., false),
+            StellariumMode::AzDegZdDeg => (360., true),
+            StellariumMode::AzRadAltRad => (TAU, false),
+            StellariumMode::AzRadZdRad => (TAU, true),
+            StellariumMode::AzGradAltGrad => (400., false),
+            StellariumMode::AzGradZdGrad => (400., true),
+        };
+
+        let to_radians = |angle: f64| angle * TAU / per_turn;
+        let altitude = if zenith_distance {
+            per_turn / 4. - second
+        } else {
+            second
+        };
+
+        (to_radians(first), to_radians(altitude))
+    }
+}
+
+impl FromStr for StellariumMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "azDeg_altDeg" => Ok(StellariumMode::AzDegAltDeg),
+            "azDeg_zdDeg" => Ok(StellariumMode::AzDegZdDeg),
+            "azRad_altRad" => Ok(StellariumMode::AzRadAltRad),
+            "azRad_zdRad" => Ok(StellariumMode::AzRadZdRad),
+            "azGrad_altGrad" => Ok(StellariumMode::AzGradAltGrad),
+            "azGrad_zdGrad" => Ok(StellariumMode::AzGradZdGrad),
+            _ => Err(anyhow!("Unknown Stellarium horizon list mode '{s}'")),
+        }
+    }
+}
+
+/// Lines with content, without comments starting with `#`, numbered from one
+fn content_lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.split('#').next().unwrap_or_default().trim()))
+        .filter(|(_, line)| !line.is_empty())
+}
+
+/// The two numbers of a line separated by a comma, a semicolon or whitespace
+fn parse_pair(line: &str) -> Option<(f64, f64)> {
+    let mut values = line
+        .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .map(|value| value.parse::<f64>());
+
+    match (values.next(), values.next()) {
+        (Some(Ok(first)), Some(Ok(second))) => Some((first, second)),
+        _ => None,
+    }
+}
+
+/// A horizon through the points at azimuths and altitudes (in radians) in any order
+fn from_points(mut points: Vec<(f64, f64)>) -> Result<Horizon, Error> {
+    ensure!(
+        !points.is_empty(),
+        "Expected at least one point of the horizon"
+    );
+
+    for (azimuth, _) in &mut points {
+        *azimuth = azimuth.rem_euclid(TAU);
+        if TAU - *azimuth < SAME_AZIMUTH {
+            *azimuth = 0.;
+        }
+    }
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    points.dedup_by(|b, a| (b.0 - a.0).abs() < SAME_AZIMUTH);
+
+    let (azimuths, altitudes) = points.into_iter().unzip();
+    Ok(Horizon::with_azimuths(azimuths, altitudes)?.resample(HORIZON_SAMPLES))
+}
+
+impl Horizon {
+    /// Parse one altitude (in radians) per line at equally spaced azimuths starting at north
+    pub fn from_dat(text: &str) -> Result<Horizon, Error> {
+        let altitudes = content_lines(text)
+            .map(|(number, line)| {
+                line.parse()
+                    .map_err(|err| anyhow!("Line {number}: invalid altitude '{line}': {err}"))
+            })
+            .collect::<Result<Vec<f64>, _>>()?;
+        ensure!(!altitudes.is_empty(), "Expected at least one altitude");
+
+        let horizon = Horizon::new(altitudes);
+        Ok(if horizon.samples() == HORIZON_SAMPLES {
+            horizon
+        } else {
+            horizon.resample(HORIZON_SAMPLES)
+        })
+    }
+
+    /// One altitude (in radians) per line at equally spaced azimuths starting at north
+    pub fn to_dat(&self) -> String {
+        let horizon = match self.azimuths {
+            Some(_) => self.resample(HORIZON_SAMPLES),
+            None => self.clone(),
+        };
+
+        let mut text = String::new();
+        for altitude in &horizon.altitudes {
+            writeln!(text, "{altitude}").expect("writing to a string");
+        }
+
+        text
+    }
+
+    /// Parse lines of an azimuth from north towards east and an altitude (in degrees),
+    /// separated by a comma, a semicolon or whitespace. A header in the first line is skipped.
+    pub fn from_csv(text: &str) -> Result<Horizon, Error> {
+        let mut points = Vec::new();
+        for (i, (number, line)) in content_lines(text).enumerate() {
+            match parse_pair(line) {
+                Some((azimuth, altitude)) => {
+                    points.push((azimuth.to_radians(), altitude.to_radians()))
+                }
+                None if i == 0 => continue,
+                None => bail!("Line {number}: expected azimuth and altitude, had '{line}'"),
+            }
+        }
+
+        from_points(points)
+    }
+
+    /// Lines of the azimuth and the altitude (in degrees) of each sample, with a header
+    pub fn to_csv(&self) -> String {
+        self.write_points("azimuth,altitude", ',')
+    }
+
+    /// Parse the `polygonal_horizon_list` of a Stellarium landscape, lines of two values in
+    /// the units of `mode`
+    pub fn from_stellarium(text: &str, mode: StellariumMode) -> Result<Horizon, Error> {
+        let points = content_lines(text)
+            .map(|(number, line)| {
+                let (first, second) = parse_pair(line).ok_or(anyhow!(
+                    "Line {number}: expected two values of a Stellarium horizon, had '{line}'"
+                ))?;
+                Ok(mode.to_radians(first, second))
+            })
+            .collect::<Result<_, Error>>()?;
+
+        from_points(points)
+    }
+
+    /// A `polygonal_horizon_list` for a Stellarium landscape in the mode `azDeg_altDeg`
+    pub fn to_stellarium(&self) -> String {
+        self.write_points("# azimuth altitude (in degrees)", ' ')
+    }
+
+    /// The header followed by a line of the azimuth and the altitude (in degrees) of each
+    /// sample, rounded to six decimals
+    fn write_points(&self, header: &str, separator: char) -> String {
+        let mut text = format!("{header}\n");
+        for (i, altitude) in self.altitudes.iter().enumerate() {
+            let azimuth = self.azimuth(i).to_degrees();
+            writeln!(text, "{azimuth:.6}{separator}{:.6}", altitude.to_degrees())
+                .expect("writing to a string");
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::angle::{Degrees, Radians};
+    use crate::util::assert_degrees_eq;
+
+    #[test]
+    fn dat_round_trip() {
+        let text = "0.1\n0.2\n\n0.3\n0.4\n";
+        let horizon = Horizon::from_dat(text).unwrap();
+        assert_eq!(horizon.samples(), HORIZON_SAMPLES);
+        assert_degrees_eq(
+            horizon.altitude_at(Radians(TAU / 8.)),
+            Radians(0.15).to_degrees().0,
+            1e-4,
+        );
+
+        let written = Horizon::from_dat(&horizon.to_dat()).unwrap();
+        assert_eq!(written.samples(), HORIZON_SAMPLES);
+        assert_degrees_eq(
+            written.altitude_at(Radians(1.)),
+            horizon.altitude_at(Radians(1.)).to_degrees().0,
+            1e-4,
+        );
+
+        // The altitudes are written exactly
+        let altitudes: Vec<f64> = (0..HORIZON_SAMPLES)
+            .map(|i| (i as f64 * 0.123456789).sin() / 7.)
+            .collect();
+        let horizon = Horizon::new(altitudes);
+        assert_eq!(Horizon::from_dat(&horizon.to_dat()).unwrap(), horizon);
+
+        assert!(Horizon::from_dat("0.1\nhigh\n").is_err());
+        assert!(Horizon::from_dat("\n").is_err());
+    }
+
+    #[test]
+    fn parse_csv() {
+        let text = "azimuth;altitude\n\
+            # surveyed by theodolite\n\
+            270; 10\n\
+            90; 2\n\
+            180; 4\n\
+            360; 0\n";
+        let horizon = Horizon::from_csv(text).unwrap();
+
+        assert_degrees_eq(horizon.altitude_at(Radians(0.)), 0., 1e-4);
+        assert_degrees_eq(horizon.altitude_at(Degrees(135.).to_radians()), 3., 1e-4);
+        assert_degrees_eq(horizon.altitude_at(Degrees(270.).to_radians()), 10., 1e-4);
+        assert_degrees_eq(horizon.altitude_at(Degrees(315.).to_radians()), 5., 1e-4);
+
+        let written = Horizon::from_csv(&horizon.to_csv()).unwrap();
+        assert_degrees_eq(written.altitude_at(Degrees(225.).to_radians()), 7., 1e-4);
+
+        assert!(Horizon::from_csv("azimuth,altitude\n").is_err());
+        assert!(Horizon::from_csv("0,1\n90\n").is_err());
+    }
+
+    #[test]
+    fn parse_stellarium() {
+        let text = "# Stellarium landscape\n0 80\n90 85\n180 90\n270 70\n";
+        let mode: StellariumMode = "azDeg_zdDeg".parse().unwrap();
+        let horizon = Horizon::from_stellarium(text, mode).unwrap();
+
+        assert_degrees_eq(horizon.altitude_at(Radians(0.)), 10., 1e-4);
+        assert_degrees_eq(horizon.altitude_at(Degrees(270.).to_radians()), 20., 1e-4);
+        assert_degrees_eq(horizon.altitude_at(Degrees(225.).to_radians()), 10., 1e-4);
+
+        let written =
+            Horizon::from_stellarium(&horizon.to_stellarium(), StellariumMode::default()).unwrap();
+        assert_degrees_eq(written.altitude_at(Degrees(45.).to_radians()), 7.5, 1e-4);
+
+        let grad = Horizon::from_stellarium("100 10\n300 0\n", StellariumMode::AzGradAltGrad);
+        assert_degrees_eq(
+            grad.unwrap().altitude_at(Degrees(90.).to_radians()),
+            9.,
+            1e-4,
+        );
+        assert!("azDeg".parse::<StellariumMode>().is_err());
+    }
+}
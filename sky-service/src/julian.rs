@@ -4,6 +4,9 @@ const JD_SINCE_2000: f64 = 2451545.;
 const DAYS_PER_CENTURY: f64 = 36525.;
 const SECONDS_PER_DAY: f64 = (60 * 60 * 24) as f64;
 
+/// Difference between terrestrial and universal time, close to its value in the 2020s
+const DELTA_T: f64 = 69.2; // seconds
+
 /// The julian day number of the given date
 ///
 /// Source: <https://github.com/soniakeys/meeus/blob/master/v3/julian/julian.go>
@@ -44,6 +47,11 @@ pub fn day_of(time: &NaiveDateTime) -> f64 {
     ((36525 * (year + 4716)) / 100) as f64 + ((306 * (month + 1) / 10) + b) as f64 + days - 1524.5
 }
 
+/// The julian ephemeris day (terrestrial time) of the given date in universal time
+pub fn ephemeris_day_of(time: &NaiveDateTime) -> f64 {
+    day_of(time) + DELTA_T / SECONDS_PER_DAY
+}
+
 pub fn day_of_since_2000(time: &NaiveDateTime) -> f64 {
     day_of(time) - JD_SINCE_2000
 }
@@ -78,3 +86,7 @@ pub fn day_of_midnight(time: &NaiveDateTime) -> f64 {
 pub fn centuries_of_midnight_since_2000(time: &NaiveDateTime) -> f64 {
     (day_of_midnight(time) - JD_SINCE_2000) / DAYS_PER_CENTURY
 }
+
+pub fn centuries_since_2000(time: &NaiveDateTime) -> f64 {
+    day_of_since_2000(time) / DAYS_PER_CENTURY
+}
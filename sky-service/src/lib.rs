@@ -6,39 +6,175 @@ use thiserror::Error;
 #[cfg(test)]
 mod util;
 
+pub mod alignment;
 pub mod angle;
+pub mod crossing;
+pub mod eclipse;
 pub mod horizon;
 pub mod julian;
 pub mod location;
 pub mod sky;
+pub mod twilight;
 
 pub mod messaging;
 
+use angle::Radians;
+use crossing::{find_crossings, Crossing, EventKind, MIN_CROSSING_SECONDS};
+
 pub use horizon::{Horizon, HORIZON_SAMPLES};
 pub use location::Location;
-pub use sky::{SkyObject, SkyPosition};
+pub use sky::{PhysicalEphemeris, SkyObject, SkyPosition};
 
 #[derive(Serialize, Deserialize)]
 pub struct HorizonEvent {
     pub time: DateTime<Utc>,
-    pub altitude: f64,
-    pub azimuth: f64,
+    pub altitude: Radians,
+    pub azimuth: Radians,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct HorizonEvents {
     pub rise: HorizonEvent,
     pub set: HorizonEvent,
+    /// The highest point of the object, e.g. the solar noon
+    pub transit: HorizonEvent,
+    /// Time the object spends above the horizon within one period from the start of the search
+    /// (in seconds)
+    pub duration: i64,
+}
+
+/// A sampled position of an object on its path across the sky
+#[derive(Serialize, Deserialize)]
+pub struct TrackPoint {
+    pub time: DateTime<Utc>,
+    pub altitude: Radians,
+    pub azimuth: Radians,
+    /// Whether the object is above the horizon, i.e. not hidden by the terrain
+    pub visible: bool,
+}
+
+/// Outcome of searching rise and set of an object
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "state", content = "events", rename_all = "snake_case")]
+pub enum HorizonEventsResult {
+    Events(HorizonEvents),
+    /// The object does not go below the horizon, e.g. the midnight sun
+    AlwaysUp,
+    /// The object does not rise above the horizon, e.g. the polar night or a deep valley
+    AlwaysDown,
+    /// The object crosses the horizon, but no matching rise and set could be found
+    SolverFailed,
+}
+
+impl HorizonEventsResult {
+    pub fn events(&self) -> Option<&HorizonEvents> {
+        match self {
+            HorizonEventsResult::Events(events) => Some(events),
+            _ => None,
+        }
+    }
+
+    pub fn state(&self) -> HorizonEventsState {
+        match self {
+            HorizonEventsResult::Events(_) => HorizonEventsState::Events,
+            HorizonEventsResult::AlwaysUp => HorizonEventsState::AlwaysUp,
+            HorizonEventsResult::AlwaysDown => HorizonEventsState::AlwaysDown,
+            HorizonEventsResult::SolverFailed => HorizonEventsState::SolverFailed,
+        }
+    }
+}
+
+/// Outcome of searching rise and set of an object, without the events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HorizonEventsState {
+    Events,
+    AlwaysUp,
+    AlwaysDown,
+    SolverFailed,
+}
+
+impl From<Result<HorizonEvents, anyhow::Error>> for HorizonEventsResult {
+    fn from(result: Result<HorizonEvents, anyhow::Error>) -> Self {
+        match result {
+            Ok(events) => HorizonEventsResult::Events(events),
+            Err(err) => match err.downcast_ref::<HorizonEventError>() {
+                Some(HorizonEventError::AlwaysUp) => HorizonEventsResult::AlwaysUp,
+                Some(HorizonEventError::AlwaysDown) => HorizonEventsResult::AlwaysDown,
+                _ => {
+                    warn!("Could not calculate horizon events: {err}");
+                    HorizonEventsResult::SolverFailed
+                }
+            },
+        }
+    }
+}
+
+/// Physical ephemeris data of an object at the times of its horizon events
+#[derive(Serialize, Deserialize)]
+pub struct EventEphemerides<E> {
+    pub rise: E,
+    pub set: E,
+}
+
+/// A time window between two horizon events, e.g. the golden hour
+#[derive(Serialize, Deserialize)]
+pub struct HorizonWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub azimuth_start: Radians,
+    pub azimuth_end: Radians,
+}
+
+impl HorizonWindow {
+    /// The window from `start` to `end`, or `None` if `end` is not after `start`
+    pub fn between(start: &HorizonEvent, end: &HorizonEvent) -> Option<Self> {
+        if end.time <= start.time {
+            return None;
+        }
+
+        Some(Self {
+            start: start.time,
+            end: end.time,
+            azimuth_start: start.azimuth,
+            azimuth_end: end.azimuth,
+        })
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
+}
+
+/// A period during which the object is above the horizon
+#[derive(Serialize, Deserialize)]
+pub struct VisibilityInterval {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// The event starting the interval, `None` if the object is up at the start of the window
+    pub rise: Option<HorizonEvent>,
+    /// The event ending the interval, `None` if the object is still up at the end of the window
+    pub set: Option<HorizonEvent>,
+}
+
+impl VisibilityInterval {
+    pub fn duration(&self) -> Duration {
+        self.end - self.start
+    }
 }
 
 #[derive(Debug, Error)]
 pub enum HorizonEventError {
-    #[error("could not determine rise and set candidate ranges")]
-    CandidateRange,
+    #[error("found a rise without a set or a set without a rise")]
+    UnmatchedCrossing,
+    #[error("the object crosses the horizon, but no crossing was found")]
+    MissedCrossing,
+    #[error("the object stays above the horizon")]
+    AlwaysUp,
+    #[error("the object stays below the horizon")]
+    AlwaysDown,
 }
 
-type CandidateRange = (NaiveDateTime, NaiveDateTime);
-
 pub fn calculate_rise_and_set<O>(
     object: &O,
     time: &NaiveDateTime,
@@ -48,137 +184,305 @@ pub fn calculate_rise_and_set<O>(
 where
     O: SkyObject,
 {
-    let (rise_range, set_range) = calculate_candidate_ranges(object, time, location, horizon)?;
+    calculate_rise_and_set_with_offset(object, time, location, horizon, Radians(0.))
+}
+
+/// Calculate the times at which the object crosses a line `offset` above the horizon.
+/// Negative offsets describe a line below the horizon, e.g. the twilight boundaries.
+pub fn calculate_rise_and_set_with_offset<O>(
+    object: &O,
+    time: &NaiveDateTime,
+    location: &Location,
+    horizon: &Horizon,
+    offset: Radians,
+) -> Result<HorizonEvents, anyhow::Error>
+where
+    O: SkyObject,
+{
+    let end = *time + object.period();
+    let min_duration = Duration::try_seconds(MIN_CROSSING_SECONDS).expect("constant value");
+    let crossings = find_crossings(object, time, &end, location, horizon, offset, min_duration);
 
-    let rise = calculate_horizon_point(object, rise_range, location, horizon);
-    let set = calculate_horizon_point(object, set_range, location, horizon);
+    if crossings.is_empty() {
+        return Err(classify_without_crossings(object, time, location, horizon, offset).into());
+    }
 
-    Ok(HorizonEvents { rise, set })
-}
+    let up = is_up(object, time, location, horizon, offset);
+    let intervals = intervals_between_crossings(crossings, time, &end, up);
+    // Behind a ragged horizon the object may rise and set several times within one period
+    let duration = intervals
+        .iter()
+        .fold(Duration::zero(), |sum, interval| sum + interval.duration());
+
+    let (mut rise, mut set) = (None, None);
+    for interval in intervals {
+        rise = rise.or(interval.rise);
+        set = set.or(interval.set);
+    }
+    let (Some(rise), Some(set)) = (rise, set) else {
+        return Err(HorizonEventError::UnmatchedCrossing.into());
+    };
 
-const MAX_RESOLUTION_EXP: usize = 5;
+    let transit = calculate_transit(object, time, location);
 
-#[derive(Debug, PartialEq)]
-enum CandidateType {
-    Rise,
-    Set,
+    Ok(HorizonEvents {
+        rise,
+        set,
+        transit,
+        duration: duration.num_seconds(),
+    })
 }
 
-fn calculate_candidate_ranges<O>(
+const CLASSIFICATION_SAMPLES: i32 = 16;
+
+/// Whether an object without any crossing within one period from `time` stays up or down.
+/// Only if samples across the period agree on it, otherwise a crossing was missed.
+fn classify_without_crossings<O>(
     object: &O,
     time: &NaiveDateTime,
     location: &Location,
     horizon: &Horizon,
-) -> Result<(CandidateRange, CandidateRange), HorizonEventError>
+    offset: Radians,
+) -> HorizonEventError
 where
     O: SkyObject,
 {
-    for r in 1..MAX_RESOLUTION_EXP {
-        let resolution = 2i32.pow(r as u32);
-        let duration = object.period() / resolution;
-        let candidates: Vec<(CandidateRange, CandidateType)> = (0..resolution)
-            .filter_map(|i| {
-                let left = time.checked_add_signed(duration * i)?;
-                let right = left.checked_add_signed(duration)?;
-
-                let left_up = is_up(object, &left, location, horizon);
-                let right_up = is_up(object, &right, location, horizon);
-
-                if left_up != right_up {
-                    let candidate_type = if left_up {
-                        CandidateType::Set
-                    } else {
-                        CandidateType::Rise
-                    };
-                    Some(((left, right), candidate_type))
-                } else {
-                    None
-                }
+    let step = object.period() / CLASSIFICATION_SAMPLES;
+    let states: Vec<bool> = (0..=CLASSIFICATION_SAMPLES)
+        .map(|i| is_up(object, &(*time + step * i), location, horizon, offset))
+        .collect();
+
+    if states.iter().all(|&up| up) {
+        HorizonEventError::AlwaysUp
+    } else if states.iter().all(|&up| !up) {
+        HorizonEventError::AlwaysDown
+    } else {
+        HorizonEventError::MissedCrossing
+    }
+}
+
+/// Sample the path of the object from `start` to `end` every `step`, e.g. to draw it over
+/// the horizon profile. Only the samples above the sea-level horizon are kept.
+///
+/// `step` has to be positive.
+pub fn calculate_track<O>(
+    object: &O,
+    start: &NaiveDateTime,
+    end: &NaiveDateTime,
+    step: Duration,
+    location: &Location,
+    horizon: &Horizon,
+) -> Vec<TrackPoint>
+where
+    O: SkyObject,
+{
+    let samples = (*end - *start).num_seconds() / step.num_seconds();
+
+    (0..=samples)
+        .map(|i| *start + step * i as i32)
+        .filter_map(|time| {
+            let SkyPosition { altitude, azimuth } = object.position(&time, location);
+
+            (altitude >= -location.dip()).then(|| TrackPoint {
+                time: time.and_utc(),
+                altitude,
+                azimuth,
+                visible: altitude > horizon_altitude(horizon, location, azimuth),
             })
-            .take(2)
-            .collect();
+        })
+        .collect()
+}
 
-        if candidates.len() == 2 && candidates[0].1 != candidates[1].1 {
-            if candidates[0].1 == CandidateType::Rise {
-                return Ok((candidates[0].0, candidates[1].0));
-            } else {
-                return Ok((candidates[1].0, candidates[0].0));
-            }
+const TRANSIT_SAMPLES: i32 = 24;
+
+/// Calculate the time at which the object reaches its highest altitude within one period
+/// from `time`, regardless of the horizon.
+pub fn calculate_transit<O>(object: &O, time: &NaiveDateTime, location: &Location) -> HorizonEvent
+where
+    O: SkyObject,
+{
+    let step = object.period() / TRANSIT_SAMPLES;
+    let depth_at = |time: &NaiveDateTime| -object.position(time, location).altitude.0;
+
+    let (highest, _) = (0..=TRANSIT_SAMPLES)
+        .map(|i| {
+            let time = *time + step * i;
+            (time, depth_at(&time))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .expect("at least one sample");
+
+    let start = (highest - step).max(*time);
+    let end = (highest + step).min(*time + object.period());
+    let transit = find_minimum(&depth_at, start, end);
+
+    let SkyPosition { altitude, azimuth } = object.position(&transit, location);
+    HorizonEvent {
+        time: transit.and_utc(),
+        altitude,
+        azimuth,
+    }
+}
+
+/// Ternary search for the minimum of a function which decreases and then increases between
+/// `start` and `end`, down to one second
+pub(crate) fn find_minimum<F>(
+    f: &F,
+    mut start: NaiveDateTime,
+    mut end: NaiveDateTime,
+) -> NaiveDateTime
+where
+    F: Fn(&NaiveDateTime) -> f64,
+{
+    while (end - start).num_seconds() > 1 {
+        let third = (end - start) / 3;
+        let (left, right) = (start + third, end - third);
+
+        if f(&left) < f(&right) {
+            end = right;
+        } else {
+            start = left;
         }
     }
 
-    Err(HorizonEventError::CandidateRange)
+    start + (end - start) / 2
 }
 
-const TOLERANCE: f64 = 1e-3; // About 0.06°
+pub fn calculate_event_ephemerides<O>(
+    object: &O,
+    events: &HorizonEvents,
+    location: &Location,
+) -> EventEphemerides<O::Ephemeris>
+where
+    O: PhysicalEphemeris,
+{
+    EventEphemerides {
+        rise: object.ephemeris(&events.rise.time.naive_utc(), location),
+        set: object.ephemeris(&events.set.time.naive_utc(), location),
+    }
+}
 
-fn calculate_horizon_point<O>(
+/// Calculate all periods between `start` and `end` during which the object is above the horizon,
+/// in chronological order.
+///
+/// Periods shorter than a minute in which the object is visible or hidden, e.g. behind
+/// a narrow peak, may go unnoticed.
+pub fn calculate_visibility_intervals<O>(
     object: &O,
-    range: CandidateRange,
+    start: &NaiveDateTime,
+    end: &NaiveDateTime,
     location: &Location,
     horizon: &Horizon,
-) -> HorizonEvent
+) -> Vec<VisibilityInterval>
 where
     O: SkyObject,
 {
-    let (mut left, mut right) = range;
-
-    // If the left altitude is less than the horizon, we are searching for a rise
-    // Swapping left and right will allow us to reuse the algorithm for finding a set below
-    let SkyPosition { altitude, azimuth } = object.position(&left, location);
-    let left_horizon_altitude = horizon.altitude_at(azimuth);
-    if altitude < left_horizon_altitude {
-        (right, left) = (left, right);
-    }
-
-    loop {
-        let difference = right - left;
-        let middle = left
-            .checked_add_signed(difference / 2)
-            .expect("should never overflow");
-
-        let SkyPosition { altitude, azimuth } = object.position(&middle, location);
-        let target_altitude = horizon.altitude_at(azimuth);
-
-        if (left - right).num_milliseconds().abs()
-            < Duration::try_seconds(1)
-                .expect("constant value")
-                .num_milliseconds()
-        {
-            warn!("Below 1s interval: {middle},");
-            warn!("{altitude}, target: {target_altitude}");
-            return HorizonEvent {
-                time: middle.and_utc(),
-                altitude,
-                azimuth,
-            };
-        }
+    let min_duration = Duration::try_seconds(MIN_CROSSING_SECONDS).expect("constant value");
+    let crossings = find_crossings(
+        object,
+        start,
+        end,
+        location,
+        horizon,
+        Radians(0.),
+        min_duration,
+    );
+    let up = is_up(object, start, location, horizon, Radians(0.));
+
+    intervals_between_crossings(crossings, start, end, up)
+}
 
-        if (altitude - target_altitude).abs() < TOLERANCE {
-            return HorizonEvent {
-                time: middle.and_utc(),
-                altitude,
-                azimuth,
-            };
-        } else if altitude > target_altitude {
-            left = middle;
-        } else {
-            right = middle;
+/// The periods between `start` and `end` during which the object is up, from its crossings
+/// in chronological order and whether it is up at `start`
+fn intervals_between_crossings(
+    crossings: Vec<Crossing>,
+    start: &NaiveDateTime,
+    end: &NaiveDateTime,
+    up: bool,
+) -> Vec<VisibilityInterval> {
+    let mut intervals = Vec::new();
+    // `Some` while the object is up, holding the event at which it rose
+    let mut rise: Option<Option<HorizonEvent>> = up.then_some(None);
+
+    for Crossing { kind, event } in crossings {
+        match (kind, rise.take()) {
+            (EventKind::Set, Some(rise)) => intervals.push(VisibilityInterval {
+                start: rise.as_ref().map_or(start.and_utc(), |rise| rise.time),
+                end: event.time,
+                rise,
+                set: Some(event),
+            }),
+            (EventKind::Rise, _) => rise = Some(Some(event)),
+            (EventKind::Set, None) => {}
         }
     }
+
+    if let Some(rise) = rise {
+        intervals.push(VisibilityInterval {
+            start: rise.as_ref().map_or(start.and_utc(), |rise| rise.time),
+            end: end.and_utc(),
+            rise,
+            set: None,
+        });
+    }
+
+    intervals
+}
+
+/// Periods during which a spot lies in direct sunlight
+#[derive(Serialize, Deserialize)]
+pub struct Sunlight {
+    pub intervals: Vec<VisibilityInterval>,
+    /// Total time in sunlight (in minutes)
+    pub minutes: i64,
 }
 
-fn is_up<O>(object: &O, time: &NaiveDateTime, location: &Location, horizon: &Horizon) -> bool
+/// Calculate all periods between `start` and `end` during which the sun is not hidden
+/// by the terrain.
+pub fn calculate_sunlight<O>(
+    sun: &O,
+    start: &NaiveDateTime,
+    end: &NaiveDateTime,
+    location: &Location,
+    horizon: &Horizon,
+) -> Sunlight
 where
     O: SkyObject,
 {
-    let SkyPosition {
-        altitude: obj_altitude,
-        azimuth,
-    } = object.position(time, location);
-    let hor_altitude = horizon.altitude_at(azimuth.to_radians());
+    let intervals = calculate_visibility_intervals(sun, start, end, location, horizon);
+    let minutes = intervals
+        .iter()
+        .map(|interval| interval.duration().num_seconds())
+        .sum::<i64>()
+        / 60;
+
+    Sunlight { intervals, minutes }
+}
 
-    obj_altitude > hor_altitude
+/// Whether the object is above a line `offset` above the horizon
+fn is_up<O>(
+    object: &O,
+    time: &NaiveDateTime,
+    location: &Location,
+    horizon: &Horizon,
+    offset: Radians,
+) -> bool
+where
+    O: SkyObject,
+{
+    let SkyPosition { altitude, azimuth } = object.position(time, location);
+
+    altitude > horizon_altitude(horizon, location, azimuth) + offset
+}
+
+/// Altitude of the horizon at the azimuth, which can not lie below the sea-level horizon
+/// seen from the elevation of the location
+pub(crate) fn horizon_altitude(
+    horizon: &Horizon,
+    location: &Location,
+    azimuth: Radians,
+) -> Radians {
+    horizon.altitude_at(azimuth).max(-location.dip())
 }
 
 #[cfg(test)]
@@ -188,10 +492,13 @@ mod test {
     use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 
     use crate::{
-        calculate_candidate_ranges,
+        angle::Radians,
+        calculate_rise_and_set,
+        crossing::{find_crossings, EventKind, MIN_CROSSING_SECONDS},
         horizon::{Horizon, HORIZON_SAMPLES},
         location::Location,
         sky::{SkyObject, SkyPosition},
+        HorizonEventsResult,
     };
 
     const SECONDS_IN_DAY: u32 = 24 * 60 * 60;
@@ -213,12 +520,61 @@ mod test {
             let azimuth = 2. * PI * (seconds / SECONDS_IN_DAY as f64);
             let altitude = -(PI / 2.) * azimuth.cos();
 
-            SkyPosition { altitude, azimuth }
+            SkyPosition {
+                altitude: Radians(altitude),
+                azimuth: Radians(azimuth),
+            }
+        }
+
+        fn max_angular_speed(&self) -> f64 {
+            PI * PI / SECONDS_IN_DAY as f64
+        }
+    }
+
+    /// Up for a few minutes only, while claiming to move so slowly that the search for
+    /// crossings steps over it
+    struct FlashingSkyObject;
+
+    impl SkyObject for FlashingSkyObject {
+        fn period(&self) -> Duration {
+            Duration::try_days(1).expect("constant value")
+        }
+
+        fn position(&self, time: &NaiveDateTime, _location: &Location) -> SkyPosition {
+            let seconds = time.num_seconds_from_midnight();
+            let altitude = if (5000..5800).contains(&seconds) {
+                0.1
+            } else {
+                -0.1
+            };
+
+            SkyPosition {
+                altitude: Radians(altitude),
+                azimuth: Radians(0.),
+            }
+        }
+
+        fn max_angular_speed(&self) -> f64 {
+            1e-9
         }
     }
 
     #[test]
-    fn candidate_ranges_flat() {
+    fn missed_crossing_is_a_solver_failure() {
+        let horizon = Horizon::new([0.; HORIZON_SAMPLES]);
+        let time = NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(2006, 8, 6).unwrap(),
+            NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+        );
+        let location = Location::default();
+
+        let result: HorizonEventsResult =
+            calculate_rise_and_set(&FlashingSkyObject, &time, &location, &horizon).into();
+        assert!(matches!(result, HorizonEventsResult::SolverFailed));
+    }
+
+    #[test]
+    fn crossings_flat() {
         let altitudes = [0.; HORIZON_SAMPLES];
         let horizon = Horizon::new(altitudes);
 
@@ -230,21 +586,31 @@ mod test {
         let location = Location {
             lat: 48.1,
             lon: 11.6,
+            ..Default::default()
         };
 
-        let (rise_range, set_range) =
-            calculate_candidate_ranges(&TestSkyObject, &time, &location, &horizon).unwrap();
-
-        assert_eq!(0, rise_range.0.hour());
-        assert_eq!(0, rise_range.0.minute());
-
-        assert_eq!(12, rise_range.1.hour());
-        assert_eq!(0, rise_range.1.minute());
-
-        assert_eq!(12, set_range.0.hour());
-        assert_eq!(0, set_range.0.minute());
+        let end = time + TestSkyObject.period();
+        let min_duration = Duration::try_seconds(MIN_CROSSING_SECONDS).unwrap();
+        let crossings = find_crossings(
+            &TestSkyObject,
+            &time,
+            &end,
+            &location,
+            &horizon,
+            Radians(0.),
+            min_duration,
+        );
 
-        assert_eq!(0, set_range.1.hour());
-        assert_eq!(0, set_range.1.minute());
+        let crossings: Vec<_> = crossings
+            .iter()
+            .map(|crossing| (crossing.kind, crossing.event.time.time()))
+            .collect();
+        assert_eq!(
+            crossings,
+            [
+                (EventKind::Rise, NaiveTime::from_hms_opt(6, 0, 0).unwrap()),
+                (EventKind::Set, NaiveTime::from_hms_opt(18, 0, 0).unwrap()),
+            ]
+        );
     }
 }
@@ -1,7 +1,108 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::angle::{Degrees, Radians};
+
+/// Pressure at sea level (in hPa) assumed by the refraction formula
+pub const STANDARD_PRESSURE: f64 = 1010.;
+/// Temperature at sea level (in °C) assumed by the refraction formula
+pub const STANDARD_TEMPERATURE: f64 = 10.;
+
+/// Decrease of the temperature with the elevation in the standard atmosphere (in K per metre)
+const LAPSE_RATE: f64 = 0.0065;
+const BAROMETRIC_EXPONENT: f64 = 5.25588;
+/// Zero degrees Celsius in kelvin
+pub(crate) const KELVIN: f64 = 273.15;
+
+/// Mean radius of the earth (in metres)
+pub const EARTH_RADIUS: f64 = 6_371_000.;
+/// Share of the curvature of the earth compensated by terrestrial refraction
+pub const TERRESTRIAL_REFRACTION: f64 = 0.13;
+
+/// Dip of the horizon per square root of the elevation, including terrestrial refraction
+const DIP_PER_SQRT_METRE: f64 = 1.76 / 60.; // degrees
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct Location {
     pub lat: f64,
     pub lon: f64,
+    /// Elevation above sea level (in metres)
+    #[serde(default)]
+    pub elevation: f64,
+    /// Atmospheric pressure (in hPa)
+    pub pressure: Option<f64>,
+    /// Air temperature (in °C)
+    pub temperature: Option<f64>,
+}
+
+impl Location {
+    /// The given air temperature (in °C), or the one of the standard atmosphere at the elevation
+    pub fn temperature(&self) -> f64 {
+        self.temperature
+            .unwrap_or(STANDARD_TEMPERATURE - LAPSE_RATE * self.elevation)
+    }
+
+    /// The given atmospheric pressure (in hPa), or the one of the standard atmosphere
+    /// at the elevation
+    pub fn pressure(&self) -> f64 {
+        self.pressure.unwrap_or_else(|| {
+            let sea_level_temperature = STANDARD_TEMPERATURE + KELVIN;
+            STANDARD_PRESSURE
+                * (1. - LAPSE_RATE * self.elevation / sea_level_temperature)
+                    .powf(BAROMETRIC_EXPONENT)
+        })
+    }
+
+    /// How far the sea-level horizon lies below the astronomical horizon
+    pub fn dip(&self) -> Radians {
+        Degrees(DIP_PER_SQRT_METRE * self.elevation.max(0.).sqrt()).to_radians()
+    }
+
+    /// Great-circle distance to the point at `lat` and `lon` (in metres)
+    pub fn distance(&self, lat: f64, lon: f64) -> f64 {
+        let (lat1, lon1) = (self.lat.to_radians(), self.lon.to_radians());
+        let (lat2, lon2) = (lat.to_radians(), lon.to_radians());
+
+        // Haversine formula
+        let a = ((lat2 - lat1) / 2.).sin().powi(2)
+            + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.).sin().powi(2);
+        2. * EARTH_RADIUS * a.sqrt().asin()
+    }
+}
+
+/// Altitude at which a point `height` metres above the observer and `distance` metres away
+/// appears, lowered by the curvature of the earth and raised by terrestrial refraction
+/// compensating the share `refraction` of it
+pub fn apparent_altitude(height: f64, distance: f64, refraction: f64) -> Radians {
+    let curvature = (1. - refraction) * distance / (2. * EARTH_RADIUS);
+    Radians(height.atan2(distance) - curvature)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn standard_atmosphere() {
+        let sea_level = Location::default();
+        assert_eq!(sea_level.pressure(), STANDARD_PRESSURE);
+        assert_eq!(sea_level.temperature(), STANDARD_TEMPERATURE);
+        assert_eq!(sea_level.dip(), Radians(0.));
+
+        let mountain = Location {
+            elevation: 2000.,
+            ..Default::default()
+        };
+        assert!((mountain.pressure() - 789.).abs() < 1.);
+        assert!((mountain.temperature() - -3.).abs() < 1e-9);
+        assert!((mountain.dip().to_degrees().0 - 1.312).abs() < 1e-3);
+
+        let measured = Location {
+            elevation: 2000.,
+            pressure: Some(820.),
+            temperature: Some(11.),
+            ..Default::default()
+        };
+        assert_eq!(measured.pressure(), 820.);
+        assert_eq!(measured.temperature(), 11.);
+    }
 }
@@ -6,11 +6,11 @@ use sky_service::messaging;
 async fn main() {
     env_logger::init();
 
-    let (jetstream, store) = messaging::setup_nats().await;
+    let (jetstream, store, config) = messaging::setup_nats().await;
     let messages = messaging::messages(&jetstream).await;
 
     // Somehow generate in function
-    let handle_message_res = messaging::generate_handle_message_res(&jetstream, &store);
+    let handle_message_res = messaging::generate_handle_message_res(&jetstream, &store, &config);
 
     messages.for_each_concurrent(16, handle_message_res).await;
 }
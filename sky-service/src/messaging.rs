@@ -3,19 +3,31 @@ use async_nats::{
     jetstream::{consumer::pull::MessagesError, kv::Store, Context, Message},
     Error,
 };
-use chrono::{DateTime, NaiveDateTime, Timelike, Utc};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc};
 use chrono_tz::Tz;
 use futures_util::Future;
-use log::{error, info};
+use log::{error, info, warn};
 use messages_common::MessageStream;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::str::FromStr;
-use std::{pin::Pin, str};
+use std::{env, pin::Pin, str};
 
 use crate::{
-    sky::{moon::Moon, sun::Sun},
-    Horizon, HorizonEvents, Location,
+    alignment::{self, AzimuthMatch, AzimuthRange, Landmark},
+    angle::Radians,
+    eclipse::{self, Eclipse},
+    horizon::analysis::HorizonSummary,
+    sky::{
+        catalog,
+        moon::{LunarModel, MoonPhase},
+        planet::Planet,
+        sun::SolarModel,
+        Contact, Limb,
+    },
+    twilight::{self, LightWindows, TwilightEvents},
+    EventEphemerides, Horizon, HorizonEvent, HorizonEventsResult, Location, SkyObject, Sunlight,
+    TrackPoint, VisibilityInterval,
 };
 
 const IN_STREAM: &str = "HORIZONS";
@@ -23,11 +35,51 @@ const HORIZON_STORE: &str = "horizons";
 const GROUP: &str = "sun-service";
 
 const OUT_STREAM: &str = "SUNSETS";
+const MAX_SERIES_DAYS: u32 = 366;
+const MAX_ECLIPSE_DAYS: u32 = 3653;
 const ERR_STREAM: &str = "ERRORS";
 
-pub async fn setup_nats() -> (Context, Store) {
+/// Horizons computed more than this many days ago are rejected, any age is accepted if unset
+const HORIZON_MAX_AGE_VAR: &str = "HORIZON_MAX_AGE_DAYS";
+
+/// Settings of the service, read from the environment once at startup
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Config {
+    /// Model for queries which do not choose one
+    pub solar_model: SolarModel,
+    /// Model for queries which do not choose one
+    pub lunar_model: LunarModel,
+    /// Horizons created longer ago are rejected, any age is accepted if `None`
+    pub max_horizon_age: Option<Duration>,
+}
+
+impl Config {
+    pub fn from_env() -> Self {
+        Config {
+            solar_model: SolarModel::from_env(),
+            lunar_model: LunarModel::from_env(),
+            max_horizon_age: max_horizon_age(),
+        }
+    }
+}
+
+/// Maximum age of a horizon from the environment
+fn max_horizon_age() -> Option<Duration> {
+    let days: i64 = env::var(HORIZON_MAX_AGE_VAR)
+        .ok()?
+        .parse()
+        .inspect_err(|err| warn!("Ignoring {HORIZON_MAX_AGE_VAR}: {err}"))
+        .ok()?;
+
+    Duration::try_days(days)
+}
+
+pub async fn setup_nats() -> (Context, Store, Config) {
     info!("Setting up NATS");
 
+    let config = Config::from_env();
+    info!("Using {config:?}");
+
     let jetstream = messages_common::connect_jetstream().await;
 
     messages_common::create_stream(&jetstream, OUT_STREAM).await;
@@ -35,7 +87,7 @@ pub async fn setup_nats() -> (Context, Store) {
 
     let store = messages_common::connect_kv_store(&jetstream, HORIZON_STORE).await;
 
-    (jetstream, store)
+    (jetstream, store, config)
 }
 
 pub async fn messages(jetstream: &Context) -> MessageStream {
@@ -48,6 +100,7 @@ type HandleMessageFun<'a> =
 pub fn generate_handle_message_res<'a>(
     jetstream: &'a Context,
     store: &'a Store,
+    config: &'a Config,
 ) -> HandleMessageFun<'a> {
     Box::new(move |message| {
         Box::pin(async move {
@@ -55,7 +108,7 @@ pub fn generate_handle_message_res<'a>(
 
             match message {
                 Ok(message) => {
-                    let res = handle_message(&message, jetstream, store).await;
+                    let res = handle_message(&message, jetstream, store, config).await;
                     if let Err(err) = res {
                         error!("Could not handle received message: {err}");
                         send_error_message(jetstream, Some(message), err)
@@ -78,6 +131,59 @@ pub fn generate_handle_message_res<'a>(
 struct SearchQuery {
     time: DateTime<Utc>,
     timezone: Tz,
+    #[serde(default)]
+    objects: Vec<String>,
+    solar_model: Option<SolarModel>,
+    lunar_model: Option<LunarModel>,
+    /// Point of the discs of the sun and the moon defining their rise and set
+    contact: Option<Contact>,
+    /// Number of consecutive days for which a series of sun and moon events is calculated
+    days: Option<u32>,
+    /// Search for rises and sets of the sun and the moon within an azimuth range
+    azimuth_search: Option<AzimuthSearch>,
+    /// Search for the sun and the moon passing behind a landmark
+    landmark_search: Option<LandmarkSearch>,
+    /// Number of consecutive days in which solar and lunar eclipses are searched
+    eclipse_days: Option<u32>,
+    /// Interval (in minutes) at which the paths of the sun and the moon are sampled
+    track_step: Option<u32>,
+    /// Day in the timezone of the query for which the sunlit periods are calculated
+    sunlight_date: Option<NaiveDate>,
+    /// Whether features of the horizon of each spot are added to the output
+    #[serde(default)]
+    horizon_summary: bool,
+    /// Whether the rises and sets of the planets are calculated
+    #[serde(default)]
+    planets: bool,
+    /// Whether the civil, nautical and astronomical twilight events are calculated
+    #[serde(default)]
+    twilight: bool,
+    /// Whether the golden and blue hour are calculated
+    #[serde(default)]
+    light_windows: bool,
+    /// Whether all periods of the day during which the sun and the moon are up are calculated
+    #[serde(default)]
+    intervals: bool,
+    /// Atmospheric pressure (in hPa), for spots which do not carry their own
+    pressure: Option<f64>,
+    /// Air temperature (in °C), for spots which do not carry their own
+    temperature: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AzimuthSearch {
+    range: AzimuthRange,
+    /// Number of consecutive days to search
+    days: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct LandmarkSearch {
+    landmark: Landmark,
+    /// Largest angular distance between the object and the top of the landmark
+    max_distance: Radians,
+    /// Number of consecutive days to search
+    days: u32,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -93,16 +199,99 @@ struct InMessage {
     spot: Spot,
 }
 
+#[derive(Serialize, Deserialize)]
+struct PlanetEvents {
+    mercury: HorizonEventsResult,
+    venus: HorizonEventsResult,
+    mars: HorizonEventsResult,
+    jupiter: HorizonEventsResult,
+    saturn: HorizonEventsResult,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CatalogObjectEvents {
+    name: String,
+    /// `None` if the name is not in the catalog
+    events: Option<HorizonEventsResult>,
+}
+
+/// All periods of the day during which the sun and the moon are above the horizon
+#[derive(Serialize, Deserialize)]
+struct VisibilityIntervals {
+    sun: Vec<VisibilityInterval>,
+    moon: Vec<VisibilityInterval>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AzimuthMatches {
+    sun: Vec<AzimuthMatch>,
+    moon: Vec<AzimuthMatch>,
+}
+
+/// Results which are only calculated on request, each is added to the output message
+/// under its own key if present
+#[derive(Serialize)]
+struct RequestedOutput {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    windows: Option<LightWindows>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    series: Option<Vec<DayEvents>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    azimuth_matches: Option<AzimuthMatches>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    landmark_alignments: Option<LandmarkAlignments>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    eclipses: Option<Vec<Eclipse>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tracks: Option<Tracks>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sunlight: Option<Sunlight>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    horizon_summary: Option<HorizonSummary>,
+}
+
+/// Paths of the sun and the moon across the sky during one day from the requested time
+#[derive(Serialize, Deserialize)]
+struct Tracks {
+    sun: Vec<TrackPoint>,
+    moon: Vec<TrackPoint>,
+}
+
+/// Closest approaches of the sun and the moon to a landmark
+#[derive(Serialize, Deserialize)]
+struct LandmarkAlignments {
+    sun: Vec<HorizonEvent>,
+    moon: Vec<HorizonEvent>,
+}
+
+/// Sun and moon events of a single day in a series
+#[derive(Serialize, Deserialize)]
+struct DayEvents {
+    /// The date in the timezone of the query
+    date: NaiveDate,
+    sun: HorizonEventsResult,
+    moon: HorizonEventsResult,
+}
+
 #[derive(Serialize, Deserialize)]
 struct OutEvents {
-    sun: Option<HorizonEvents>,
-    moon: Option<HorizonEvents>,
+    sun: HorizonEventsResult,
+    moon: HorizonEventsResult,
+    moon_phase: Option<EventEphemerides<MoonPhase>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    twilight: Option<TwilightEvents>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    planets: Option<PlanetEvents>,
+    objects: Vec<CatalogObjectEvents>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    intervals: Option<VisibilityIntervals>,
 }
 
 pub async fn handle_message(
     message: &Message,
     jetstream: &Context,
     store: &Store,
+    config: &Config,
 ) -> Result<(), Error> {
     let payload = str::from_utf8(&message.payload)?;
     let decoded_message: InMessage = serde_json::from_str(payload)?;
@@ -118,21 +307,243 @@ pub async fn handle_message(
     );
 
     let time = get_time(&decoded_message);
-    let sun_events =
-        crate::calculate_rise_and_set(&Sun, &time, &decoded_message.spot.loc, &horizon).ok();
-    let moon_events =
-        crate::calculate_rise_and_set(&Moon, &time, &decoded_message.spot.loc, &horizon).ok();
+    let location = get_location(&decoded_message);
+    horizon
+        .check(
+            &location,
+            config.max_horizon_age.map(|age| Utc::now() - age),
+        )
+        .map_err(|err| anyhow!("Rejected horizon '{}': {err}", decoded_message.horizon))?;
+    let sun = decoded_message
+        .search_query
+        .solar_model
+        .unwrap_or(config.solar_model);
+    let moon = decoded_message
+        .search_query
+        .lunar_model
+        .unwrap_or(config.lunar_model);
+    let contact = decoded_message.search_query.contact.unwrap_or_default();
+    let sun_events: HorizonEventsResult =
+        crate::calculate_rise_and_set(&Limb::new(&sun, contact), &time, &location, &horizon).into();
+    let moon_events: HorizonEventsResult =
+        crate::calculate_rise_and_set(&Limb::new(&moon, contact), &time, &location, &horizon)
+            .into();
+    let moon_phase = moon_events
+        .events()
+        .map(|events| crate::calculate_event_ephemerides(&moon, events, &location));
+    let query = &decoded_message.search_query;
+    let twilight_events = query
+        .twilight
+        .then(|| twilight::calculate_twilight_events(&sun, &time, &location, &horizon));
+    let windows = query
+        .light_windows
+        .then(|| twilight::calculate_light_windows(&sun, &time, &location, &horizon));
+    let planet_events =
+        |planet: Planet| crate::calculate_rise_and_set(&planet, &time, &location, &horizon).into();
+    let planets = query.planets.then(|| PlanetEvents {
+        mercury: planet_events(Planet::Mercury),
+        venus: planet_events(Planet::Venus),
+        mars: planet_events(Planet::Mars),
+        jupiter: planet_events(Planet::Jupiter),
+        saturn: planet_events(Planet::Saturn),
+    });
+    let objects = calculate_catalog_events(
+        &decoded_message.search_query.objects,
+        &time,
+        &location,
+        &horizon,
+    );
+    let end = time + Duration::try_days(1).expect("constant value");
+    let intervals = query.intervals.then(|| VisibilityIntervals {
+        sun: crate::calculate_visibility_intervals(
+            &Limb::new(&sun, contact),
+            &time,
+            &end,
+            &location,
+            &horizon,
+        ),
+        moon: crate::calculate_visibility_intervals(
+            &Limb::new(&moon, contact),
+            &time,
+            &end,
+            &location,
+            &horizon,
+        ),
+    });
+
+    let series = decoded_message
+        .search_query
+        .days
+        .map(|days| {
+            if days > MAX_SERIES_DAYS {
+                return Err(anyhow!(
+                    "A series can span at most {MAX_SERIES_DAYS} days, not {days}"
+                ));
+            }
+
+            Ok(calculate_series(
+                &Limb::new(&sun, contact),
+                &Limb::new(&moon, contact),
+                &time,
+                days,
+                &decoded_message.search_query.timezone,
+                &location,
+                &horizon,
+            ))
+        })
+        .transpose()?;
+
+    let azimuth_matches = decoded_message
+        .search_query
+        .azimuth_search
+        .as_ref()
+        .map(|search| {
+            if search.days > MAX_SERIES_DAYS {
+                return Err(anyhow!(
+                    "An azimuth search can span at most {MAX_SERIES_DAYS} days, not {}",
+                    search.days
+                ));
+            }
+
+            Ok(AzimuthMatches {
+                sun: alignment::find_events_in_azimuth_range(
+                    &Limb::new(&sun, contact),
+                    &time,
+                    search.days,
+                    &location,
+                    &horizon,
+                    &search.range,
+                ),
+                moon: alignment::find_events_in_azimuth_range(
+                    &Limb::new(&moon, contact),
+                    &time,
+                    search.days,
+                    &location,
+                    &horizon,
+                    &search.range,
+                ),
+            })
+        })
+        .transpose()?;
+
+    let landmark_alignments = decoded_message
+        .search_query
+        .landmark_search
+        .as_ref()
+        .map(|search| {
+            if search.days > MAX_SERIES_DAYS {
+                return Err(anyhow!(
+                    "A landmark search can span at most {MAX_SERIES_DAYS} days, not {}",
+                    search.days
+                ));
+            }
+
+            Ok(LandmarkAlignments {
+                sun: alignment::find_landmark_alignments(
+                    &sun,
+                    &time,
+                    search.days,
+                    &location,
+                    &search.landmark,
+                    search.max_distance,
+                ),
+                moon: alignment::find_landmark_alignments(
+                    &moon,
+                    &time,
+                    search.days,
+                    &location,
+                    &search.landmark,
+                    search.max_distance,
+                ),
+            })
+        })
+        .transpose()?;
+
+    let eclipses = decoded_message
+        .search_query
+        .eclipse_days
+        .map(|days| {
+            if days > MAX_ECLIPSE_DAYS {
+                return Err(anyhow!(
+                    "An eclipse search can span at most {MAX_ECLIPSE_DAYS} days, not {days}"
+                ));
+            }
+
+            Ok(eclipse::find_eclipses(&time, days, &location, &horizon))
+        })
+        .transpose()?;
+
+    let tracks = decoded_message
+        .search_query
+        .track_step
+        .map(|step| {
+            if step == 0 {
+                return Err(anyhow!("The step of a track has to be at least one minute"));
+            }
+            let step = Duration::try_minutes(step.into()).expect("u32 minutes fit");
+            // Unlike the events, the paths start at the requested time itself
+            let start = decoded_message.search_query.time.naive_utc();
+            let end = start + Duration::try_days(1).expect("constant value");
+
+            Ok(Tracks {
+                sun: crate::calculate_track(&sun, &start, &end, step, &location, &horizon),
+                moon: crate::calculate_track(&moon, &start, &end, step, &location, &horizon),
+            })
+        })
+        .transpose()?;
+
+    let sunlight = decoded_message
+        .search_query
+        .sunlight_date
+        .map(|date| {
+            let (start, end) = local_day(&date, &decoded_message.search_query.timezone)?;
+
+            Ok::<_, anyhow::Error>(crate::calculate_sunlight(
+                &Limb::new(&sun, contact),
+                &start,
+                &end,
+                &location,
+                &horizon,
+            ))
+        })
+        .transpose()?;
+
+    let horizon_summary = decoded_message
+        .search_query
+        .horizon_summary
+        .then(|| horizon.summary());
 
     let result = OutEvents {
         sun: sun_events,
         moon: moon_events,
+        moon_phase,
+        twilight: twilight_events,
+        planets,
+        objects,
+        intervals,
     };
 
     let in_value = Value::from_str(payload)?;
     jetstream
         .publish(
             format!("{}.{}", OUT_STREAM, decoded_message.request_id),
-            build_output(in_value, result)?.to_string().into(),
+            build_output(
+                in_value,
+                result,
+                &horizon,
+                RequestedOutput {
+                    windows,
+                    series,
+                    azimuth_matches,
+                    landmark_alignments,
+                    eclipses,
+                    tracks,
+                    sunlight,
+                    horizon_summary,
+                },
+            )?
+            .to_string()
+            .into(),
         )
         .await?;
     info!("sent out results");
@@ -142,6 +553,61 @@ pub async fn handle_message(
     Ok(())
 }
 
+/// Calculate the events of the named catalog objects. An unknown name is reported back
+/// without events, so that a typo does not cost the results of the whole spot.
+fn calculate_catalog_events(
+    names: &[String],
+    time: &NaiveDateTime,
+    location: &Location,
+    horizon: &Horizon,
+) -> Vec<CatalogObjectEvents> {
+    names
+        .iter()
+        .map(|name| match catalog::find(name) {
+            Some(entry) => CatalogObjectEvents {
+                name: entry.name.to_string(),
+                events: Some(
+                    crate::calculate_rise_and_set(&entry.object(), time, location, horizon).into(),
+                ),
+            },
+            None => {
+                warn!("Unknown catalog object '{name}'");
+                CatalogObjectEvents {
+                    name: name.clone(),
+                    events: None,
+                }
+            }
+        })
+        .collect()
+}
+
+/// Calculate the sun and moon events for `days` consecutive days starting at `time`
+fn calculate_series<S, M>(
+    sun: &S,
+    moon: &M,
+    time: &NaiveDateTime,
+    days: u32,
+    timezone: &Tz,
+    location: &Location,
+    horizon: &Horizon,
+) -> Vec<DayEvents>
+where
+    S: SkyObject,
+    M: SkyObject,
+{
+    (0..days)
+        .map(|day| {
+            let time = *time + Duration::try_days(day.into()).expect("at most MAX_SERIES_DAYS");
+
+            DayEvents {
+                date: time.and_utc().with_timezone(timezone).date_naive(),
+                sun: crate::calculate_rise_and_set(sun, &time, location, horizon).into(),
+                moon: crate::calculate_rise_and_set(moon, &time, location, horizon).into(),
+            }
+        })
+        .collect()
+}
+
 fn get_time(message: &InMessage) -> NaiveDateTime {
     let time = message.search_query.time;
     let time = time.with_timezone(&message.search_query.timezone);
@@ -155,13 +621,56 @@ fn get_time(message: &InMessage) -> NaiveDateTime {
     time.naive_utc()
 }
 
-fn build_output(in_value: Value, result: OutEvents) -> Result<Value, Error> {
+/// Start and end of the date in the timezone, in UTC
+fn local_day(
+    date: &NaiveDate,
+    timezone: &Tz,
+) -> Result<(NaiveDateTime, NaiveDateTime), anyhow::Error> {
+    let midnight = |date: NaiveDate| {
+        let local = date.and_time(NaiveTime::MIN);
+        // If the clocks are turned forward at midnight, the day starts an hour later
+        timezone
+            .from_local_datetime(&local)
+            .earliest()
+            .or_else(|| {
+                let later = local + Duration::try_hours(1).expect("constant value");
+                timezone.from_local_datetime(&later).earliest()
+            })
+            .map_or(local, |time| time.naive_utc())
+    };
+
+    let next = date.succ_opt().ok_or(anyhow!(
+        "The day {date} has no end, it is the last representable one"
+    ))?;
+    Ok((midnight(*date), midnight(next)))
+}
+
+fn get_location(message: &InMessage) -> Location {
+    let location = message.spot.loc;
+
+    Location {
+        pressure: location.pressure.or(message.search_query.pressure),
+        temperature: location.temperature.or(message.search_query.temperature),
+        ..location
+    }
+}
+
+fn build_output(
+    in_value: Value,
+    result: OutEvents,
+    horizon: &Horizon,
+    requested: RequestedOutput,
+) -> Result<Value, Error> {
     let mut output = in_value;
     let output_obj = output.as_object_mut().ok_or(anyhow!(
         "in message was not an object, could not build output message"
     ))?;
 
     output_obj.insert("events".to_string(), json!(result));
+    output_obj.insert("horizon_metadata".to_string(), json!(horizon.metadata()));
+    if let Value::Object(requested) = json!(requested) {
+        output_obj.extend(requested);
+    }
 
     Ok(output)
 }
@@ -202,3 +711,83 @@ fn build_error_payload(msg: &Message, error: async_nats::Error) -> String {
     })
     .to_string()
 }
+
+#[cfg(test)]
+mod test {
+    use crate::{sky::sun::Sun, HORIZON_SAMPLES};
+
+    use super::*;
+
+    #[test]
+    fn local_day_over_dst_change() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 31).unwrap();
+
+        let (start, end) = local_day(&date, &Tz::Europe__Berlin).unwrap();
+
+        assert_eq!(start.to_string(), "2024-03-30 23:00:00");
+        assert_eq!(end.to_string(), "2024-03-31 22:00:00");
+
+        assert!(local_day(&NaiveDate::MAX, &Tz::Europe__Berlin).is_err());
+        assert!(local_day(&NaiveDate::MIN, &Tz::Europe__Berlin).is_ok());
+    }
+
+    #[test]
+    fn unknown_catalog_objects() {
+        let horizon = Horizon::new([0.; HORIZON_SAMPLES]);
+        let location = Location {
+            lat: 48.1,
+            lon: 11.6,
+            ..Default::default()
+        };
+        let time = NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(2024, 3, 29).unwrap(),
+            NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+        );
+        let names = ["Pleiades".to_string(), "Pleiadess".to_string()];
+
+        let objects = calculate_catalog_events(&names, &time, &location, &horizon);
+
+        assert_eq!(objects.len(), 2);
+        assert!(objects[0].events.is_some());
+        assert_eq!(objects[1].name, "Pleiadess");
+        assert!(objects[1].events.is_none());
+    }
+
+    #[test]
+    fn series_over_dst_change() {
+        let horizon = Horizon::new([0.; HORIZON_SAMPLES]);
+        let location = Location {
+            lat: 48.1,
+            lon: 11.6,
+            ..Default::default()
+        };
+        // Local midnight of the 30th of March
+        let time = NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(2024, 3, 29).unwrap(),
+            NaiveTime::from_hms_opt(23, 0, 0).unwrap(),
+        );
+
+        let series = calculate_series(
+            &Sun,
+            &Sun,
+            &time,
+            3,
+            &Tz::Europe__Berlin,
+            &location,
+            &horizon,
+        );
+
+        let dates: Vec<_> = series.iter().map(|day| day.date.to_string()).collect();
+        assert_eq!(dates, ["2024-03-30", "2024-03-31", "2024-04-01"]);
+
+        let sunsets: Vec<_> = series
+            .iter()
+            .map(|day| day.sun.events().unwrap().set.time)
+            .collect();
+        // Sunsets move later by about two minutes each day
+        for pair in sunsets.windows(2) {
+            let difference = (pair[1] - pair[0] - Duration::try_days(1).unwrap()).num_seconds();
+            assert!((60..180).contains(&difference), "{difference}");
+        }
+    }
+}
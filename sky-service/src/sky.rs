@@ -1,18 +1,98 @@
+use std::f64::consts::TAU;
+
 use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
 
-use crate::location::Location;
+use crate::{angle::Radians, location::Location};
 
+pub mod catalog;
+pub mod fixed;
 pub mod moon;
+pub mod planet;
 pub mod sun;
-mod util;
+pub(crate) mod util;
 
 #[derive(Debug)]
 pub struct SkyPosition {
-    pub altitude: f64,
-    pub azimuth: f64,
+    pub altitude: Radians,
+    /// Measured from north towards east
+    pub azimuth: Radians,
 }
 
+/// Rotation of the sky, with some margin for refraction and parallax
+const MAX_DIURNAL_SPEED: f64 = 1.1 * TAU / 86164.;
+
 pub trait SkyObject {
     fn period(&self) -> Duration;
     fn position(&self, time: &NaiveDateTime, location: &Location) -> SkyPosition;
+
+    /// Apparent radius of the disc of the object, zero for point-like objects
+    fn angular_radius(&self, _time: &NaiveDateTime, _location: &Location) -> Radians {
+        Radians(0.)
+    }
+
+    /// Upper bound of the speed at which the object moves across the sky (in radians per
+    /// second), which paces the search for its crossings with the horizon
+    fn max_angular_speed(&self) -> f64 {
+        MAX_DIURNAL_SPEED
+    }
+}
+
+/// Objects that report physical ephemeris data (e.g. the phase of the moon)
+/// in addition to their position
+pub trait PhysicalEphemeris: SkyObject {
+    type Ephemeris;
+
+    fn ephemeris(&self, time: &NaiveDateTime, location: &Location) -> Self::Ephemeris;
+}
+
+/// The point of the disc of an object which defines its rise and set
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Contact {
+    /// The centre of the disc crosses the horizon
+    #[default]
+    Centre,
+    /// The upper limb crosses the horizon, i.e. first and last light
+    UpperLimb,
+    /// The lower limb crosses the horizon
+    LowerLimb,
+}
+
+/// An object whose position is the contact point on its disc, such that rise and set events
+/// of the limb describe the given contact
+pub struct Limb<'a, O> {
+    object: &'a O,
+    contact: Contact,
+}
+
+impl<'a, O> Limb<'a, O> {
+    pub fn new(object: &'a O, contact: Contact) -> Self {
+        Self { object, contact }
+    }
+}
+
+impl<O> SkyObject for Limb<'_, O>
+where
+    O: SkyObject,
+{
+    fn period(&self) -> Duration {
+        self.object.period()
+    }
+
+    fn max_angular_speed(&self) -> f64 {
+        self.object.max_angular_speed()
+    }
+
+    fn position(&self, time: &NaiveDateTime, location: &Location) -> SkyPosition {
+        let SkyPosition { altitude, azimuth } = self.object.position(time, location);
+
+        let altitude = match self.contact {
+            Contact::Centre => altitude,
+            Contact::UpperLimb => altitude + self.object.angular_radius(time, location),
+            Contact::LowerLimb => altitude - self.object.angular_radius(time, location),
+        };
+
+        SkyPosition { altitude, azimuth }
+    }
 }
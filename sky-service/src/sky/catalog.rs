@@ -0,0 +1,95 @@
+use super::fixed::FixedObject;
+
+/// A named bright star or deep-sky object with J2000 coordinates (in degrees)
+#[derive(Debug)]
+pub struct CatalogEntry {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub right_ascension: f64,
+    pub declination: f64,
+}
+
+impl CatalogEntry {
+    pub fn object(&self) -> FixedObject {
+        FixedObject::from_degrees(self.right_ascension, self.declination)
+    }
+
+    fn is_called(&self, name: &str) -> bool {
+        std::iter::once(&self.name)
+            .chain(self.aliases)
+            .any(|candidate| candidate.eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+const fn entry(
+    name: &'static str,
+    aliases: &'static [&'static str],
+    right_ascension: f64,
+    declination: f64,
+) -> CatalogEntry {
+    CatalogEntry {
+        name,
+        aliases,
+        right_ascension,
+        declination,
+    }
+}
+
+pub const CATALOG: &[CatalogEntry] = &[
+    // Bright stars
+    entry("Sirius", &["alpha CMa"], 101.2872, -16.7161),
+    entry("Canopus", &["alpha Car"], 95.9880, -52.6957),
+    entry("Arcturus", &["alpha Boo"], 213.9153, 19.1824),
+    entry("Vega", &["alpha Lyr"], 279.2347, 38.7837),
+    entry("Capella", &["alpha Aur"], 79.1723, 45.9980),
+    entry("Rigel", &["beta Ori"], 78.6345, -8.2016),
+    entry("Procyon", &["alpha CMi"], 114.8255, 5.2250),
+    entry("Betelgeuse", &["alpha Ori"], 88.7929, 7.4071),
+    entry("Altair", &["alpha Aql"], 297.6958, 8.8683),
+    entry("Aldebaran", &["alpha Tau"], 68.9802, 16.5093),
+    entry("Antares", &["alpha Sco"], 247.3519, -26.4320),
+    entry("Spica", &["alpha Vir"], 201.2983, -11.1613),
+    entry("Pollux", &["beta Gem"], 116.3290, 28.0262),
+    entry("Fomalhaut", &["alpha PsA"], 344.4128, -29.6222),
+    entry("Deneb", &["alpha Cyg"], 310.3580, 45.2803),
+    entry("Regulus", &["alpha Leo"], 152.0930, 11.9672),
+    entry("Polaris", &["alpha UMi", "North Star"], 37.9546, 89.2641),
+    // Asterisms and deep-sky objects
+    entry("Orion's Belt", &["Orion", "Alnilam"], 84.0533, -1.2019),
+    entry("Pleiades", &["M45", "Seven Sisters"], 56.8500, 24.1167),
+    entry("Orion Nebula", &["M42"], 83.8221, -5.3911),
+    entry("Andromeda Galaxy", &["M31"], 10.6847, 41.2691),
+    entry(
+        "Milky Way Core",
+        &["Galactic Center", "Sgr A*"],
+        266.4168,
+        -29.0078,
+    ),
+    entry("Lagoon Nebula", &["M8"], 270.9042, -24.3867),
+    entry("Beehive Cluster", &["M44", "Praesepe"], 130.1000, 19.6667),
+    entry("Double Cluster", &["NGC 869", "NGC 884"], 35.0000, 57.1333),
+    entry("Omega Centauri", &["NGC 5139"], 201.6970, -47.4795),
+    entry("Carina Nebula", &["NGC 3372"], 161.2654, -59.8678),
+    entry("Large Magellanic Cloud", &["LMC"], 80.8938, -69.7561),
+];
+
+/// Find a catalog entry by its name or one of its aliases, ignoring case
+pub fn find(name: &str) -> Option<&'static CatalogEntry> {
+    CATALOG.iter().find(|entry| entry.is_called(name))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn find_by_alias() {
+        let entry = find("m45").unwrap();
+        assert_eq!(entry.name, "Pleiades");
+
+        let entry = find(" milky way core ").unwrap();
+        assert_eq!(entry.name, "Milky Way Core");
+
+        assert!(find("Vulcan").is_none());
+    }
+}
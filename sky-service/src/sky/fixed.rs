@@ -0,0 +1,107 @@
+use std::f64::consts::PI;
+
+use chrono::{Duration, NaiveDateTime};
+
+use crate::{
+    angle::{AngleExtensions, Radians},
+    julian, Location, SkyObject, SkyPosition,
+};
+
+use super::util;
+
+const ARCSECONDS: f64 = PI / (180. * 3600.);
+
+const ZETA_C1: f64 = 2306.2181 * ARCSECONDS;
+const ZETA_C2: f64 = 0.30188 * ARCSECONDS;
+const ZETA_C3: f64 = 0.017998 * ARCSECONDS;
+const Z_C1: f64 = 2306.2181 * ARCSECONDS;
+const Z_C2: f64 = 1.09468 * ARCSECONDS;
+const Z_C3: f64 = 0.018203 * ARCSECONDS;
+const THETA_C1: f64 = 2004.3109 * ARCSECONDS;
+const THETA_C2: f64 = -0.42665 * ARCSECONDS;
+const THETA_C3: f64 = -0.041833 * ARCSECONDS;
+
+const SIDEREAL_DAY_SECONDS: i64 = 86164;
+
+/// An object with fixed equatorial coordinates, like a star or a galaxy
+#[derive(Debug, Clone, Copy)]
+pub struct FixedObject {
+    /// Right ascension at the epoch J2000 (in radians)
+    pub right_ascension: f64,
+    /// Declination at the epoch J2000 (in radians)
+    pub declination: f64,
+}
+
+impl FixedObject {
+    pub fn from_degrees(right_ascension: f64, declination: f64) -> Self {
+        Self {
+            right_ascension: right_ascension.to_radians(),
+            declination: declination.to_radians(),
+        }
+    }
+
+    /// Right ascension and declination precessed from J2000 to the given time
+    ///
+    /// source: Meeus, Astronomical Algorithms, chapter 21
+    pub fn equatorial_coordinates(&self, time: &NaiveDateTime) -> (f64, f64) {
+        let t = julian::centuries_since_2000(time);
+
+        let zeta = t * (ZETA_C1 + t * (ZETA_C2 + t * ZETA_C3));
+        let z = t * (Z_C1 + t * (Z_C2 + t * Z_C3));
+        let theta = t * (THETA_C1 + t * (THETA_C2 + t * THETA_C3));
+
+        let (alpha0, delta0) = (self.right_ascension, self.declination);
+        let (theta_sin, theta_cos) = theta.sin_cos();
+
+        let a = delta0.cos() * (alpha0 + zeta).sin();
+        let b = theta_cos * delta0.cos() * (alpha0 + zeta).cos() - theta_sin * delta0.sin();
+        let c = theta_sin * delta0.cos() * (alpha0 + zeta).cos() + theta_cos * delta0.sin();
+
+        let alpha = (a.atan2(b) + z).normalize_radians();
+        let delta = c.asin();
+
+        (alpha, delta)
+    }
+}
+
+impl SkyObject for FixedObject {
+    fn period(&self) -> Duration {
+        Duration::try_seconds(SIDEREAL_DAY_SECONDS).expect("constant value")
+    }
+
+    fn position(&self, time: &NaiveDateTime, location: &Location) -> SkyPosition {
+        let (alpha, delta) = self.equatorial_coordinates(time);
+
+        let (altitude, azimuth) =
+            util::convert_ecliptic_to_horizontal(time, location, alpha, delta);
+        let altitude = util::refraction(altitude, location);
+
+        SkyPosition {
+            altitude: Radians(altitude),
+            azimuth: Radians(azimuth),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, NaiveTime};
+
+    use super::*;
+
+    // source: Meeus, Astronomical Algorithms, example 21.b (without proper motion)
+    #[test]
+    fn precession_meeus() {
+        let theta_persei = FixedObject::from_degrees(41.049_942, 49.228_467);
+
+        let time = NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(2028, 11, 13).unwrap(),
+            NaiveTime::from_hms_opt(4, 33, 36).unwrap(),
+        );
+
+        let (alpha, delta) = theta_persei.equatorial_coordinates(&time);
+
+        assert!((alpha.to_degrees() - 41.543_096).abs() < 1e-4);
+        assert!((delta.to_degrees() - 49.349_200).abs() < 1e-4);
+    }
+}
@@ -1,10 +1,20 @@
-use std::f64::consts::PI;
+use std::{env, f64::consts::PI, str::FromStr};
 
+use anyhow::anyhow;
 use chrono::{Duration, NaiveDateTime};
+use log::warn;
+use serde::{Deserialize, Serialize};
 
-use crate::{angle::AngleExtensions, julian, Location, SkyObject, SkyPosition};
+use crate::{
+    angle::{AngleExtensions, Radians},
+    julian, Location, SkyObject, SkyPosition,
+};
 
-use super::util;
+use super::{sun, util, PhysicalEphemeris};
+
+pub mod meeus;
+
+const LUNAR_MODEL_VAR: &str = "LUNAR_MODEL";
 
 const LONG_ASC_NODE_0: f64 = 125.1228f64 * PI / 180.;
 const LONG_ASC_NODE_1: f64 = 0.0529538083 * PI / 180.;
@@ -51,101 +61,22 @@ const PERT_LAT_4: f64 = 0.017 * PI / 180.;
 const PERT_R_0: f64 = 0.58 * PI / 180.;
 const PERT_R_1: f64 = 0.46 * PI / 180.;
 
+/// Ratio of the radii of the moon and the earth
+const MOON_RADIUS: f64 = 0.272481;
+
+const SYNODIC_MONTH: f64 = 29.530588853; // days
+const SUN_DISTANCE: f64 = 23454.8; // earth radii
+
 pub struct Moon;
 impl SkyObject for Moon {
     fn period(&self) -> Duration {
         Duration::try_hours(26).expect("constant value")
     }
 
-    // source: http://www.stjarnhimlen.se/comp/tutorial.html#7
-    #[allow(non_snake_case)]
     fn position(&self, time: &NaiveDateTime, location: &Location) -> SkyPosition {
-        let d = julian::day_of(time) - 2451543.5;
-
-        let N = (LONG_ASC_NODE_0 - LONG_ASC_NODE_1 * d).normalize_radians();
-        let w = (ARG_OF_PERIGEE_0 + ARG_OF_PERIGEE_1 * d).normalize_radians();
-        let M = (MEAN_ANOMALY_0 + MEAN_ANOMALY_1 * d).normalize_radians();
-
-        // Eccentric anomaly
-        let mut E0 =
-            (M + ECCENTRICITY * M.sin() * (1. + ECCENTRICITY * M.cos())).normalize_radians();
-        loop {
-            let E1 = (E0 - (E0 - ECCENTRICITY * E0.sin() - M) / (1. - ECCENTRICITY * E0.cos()))
-                .normalize_radians();
-
-            let delta = (E0 - E1).abs();
-            E0 = E1;
-            if delta < ECCENTRICITY_EPS {
-                break;
-            }
-        }
-        let E = E0;
-
-        // Rectangular coordinates
-        let x = MEAN_DISTANCE * (E.cos() - ECCENTRICITY);
-        let y = MEAN_DISTANCE * (1. - ECCENTRICITY.powi(2)).sqrt() * E.sin();
-
-        // Distance and true anomaly
-        let r = (x.powi(2) + y.powi(2)).sqrt();
-        let v = y.atan2(x);
-
-        // Ecliptic coordinates
-        let vwsin = (v + w).sin();
-        let xeclip = r * (N.cos() * (v + w).cos() - N.sin() * vwsin * INCLINATION.cos());
-        let yeclip = r * (N.sin() * (v + w).cos() + N.cos() * vwsin * INCLINATION.cos());
-        let zeclip = r * vwsin * INCLINATION.sin();
-
-        let mut mlon = yeclip.atan2(xeclip);
-        let xeclip_yeclip_squared = xeclip.powi(2) + yeclip.powi(2);
-        let mut mlat = zeclip.atan2((xeclip_yeclip_squared).sqrt());
-        let mut r = (xeclip_yeclip_squared + zeclip.powi(2)).sqrt();
-
-        // Pertubations
-
-        let (Ls, Ms) = sun_mean_length_and_anomaly(d);
-        let Lm = N + w + M;
-        let Mm = M;
-        let D = Lm - Ls;
-        let F = Lm - N;
-
-        let d2 = 2. * d;
-        mlon += PERT_LON_0 * (M - d2).sin()
-            + PERT_LON_1 * d2.sin()
-            + PERT_LON_2 * Ms.sin()
-            + PERT_LON_3 * (2. * Mm - d2).sin()
-            + PERT_LON_4 * (Mm - d2 + Ms).sin()
-            + PERT_LON_5 * (Mm + d2).sin()
-            + PERT_LON_6 * (d2 - Ms).sin()
-            + PERT_LON_7 * (Mm - Ms).sin()
-            + PERT_LON_8 * D.sin()
-            + PERT_LON_9 * (Mm + Ms).sin()
-            + PERT_LON_10 * (2. * F - d2).sin()
-            + PERT_LON_11 * (Mm - 4. * D).sin();
-
-        mlat += PERT_LAT_0 * (F - d2).sin()
-            + PERT_LAT_1 * (Mm - F - d2).sin()
-            + PERT_LAT_2 * (Mm + F - d2).sin()
-            + PERT_LAT_3 * (F + d2).sin()
-            + PERT_LAT_4 * (2. * Mm + F).sin();
-
-        r += PERT_R_0 * (Mm - d2).cos() + PERT_R_1 * d2.cos();
-
-        // Equatorial coordinates
-        let oblecl = OBLIQUITY_ECLIPTIC_0 - OBLIQUITY_ECLIPTIC_1 * d;
-
-        let (mlon_sin, mlon_cos) = mlon.sin_cos();
-        let (mlat_sin, mlat_cos) = mlat.sin_cos();
-        let xeclip = mlon_cos * mlat_cos;
-        let yeclip = mlon_sin * mlat_cos;
-        let zeclip = mlat_sin;
-
-        let xequat = xeclip;
-        let (oblecl_sin, oblecl_cos) = oblecl.sin_cos();
-        let yequat = yeclip * oblecl_cos - zeclip * oblecl_sin;
-        let zequat = yeclip * oblecl_sin + zeclip * oblecl_cos;
-
-        let alpha = yequat.atan2(xequat);
-        let delta = zequat.atan2((xequat.powi(2) + yequat.powi(2)).sqrt());
+        let GeocentricCoordinates {
+            alpha, delta, r, ..
+        } = geocentric_coordinates(time);
 
         // Ecliptic coordinates for the observer
         let mpar = (1. / r).asin();
@@ -156,9 +87,277 @@ impl SkyObject for Moon {
         // Correct for parallax
         let altitude = altitude - mpar * altitude.cos();
 
-        let altitude = util::refraction(altitude);
+        let altitude = util::refraction(altitude, location);
+
+        SkyPosition {
+            altitude: Radians(altitude),
+            azimuth: Radians(azimuth),
+        }
+    }
+
+    fn angular_radius(&self, time: &NaiveDateTime, _location: &Location) -> Radians {
+        let GeocentricCoordinates { r, .. } = geocentric_coordinates(time);
+
+        Radians((MOON_RADIUS / r).asin())
+    }
+}
+
+/// The model used for calculating the position of the moon
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LunarModel {
+    /// Short series of perturbations, see [`Moon`]
+    #[default]
+    Simple,
+    /// Main terms of the ELP-2000/82 theory, see [`meeus::MeeusMoon`]
+    Meeus,
+}
+
+impl LunarModel {
+    /// The model configured by the environment variable `LUNAR_MODEL`,
+    /// falls back to the default model if it is not set or invalid
+    pub fn from_env() -> Self {
+        env::var(LUNAR_MODEL_VAR)
+            .ok()
+            .and_then(|value| {
+                value
+                    .parse()
+                    .inspect_err(|err| warn!("Ignoring {LUNAR_MODEL_VAR}: {err}"))
+                    .ok()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl FromStr for LunarModel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "simple" => Ok(LunarModel::Simple),
+            "meeus" => Ok(LunarModel::Meeus),
+            _ => Err(anyhow!("unknown lunar model '{s}'")),
+        }
+    }
+}
+
+impl SkyObject for LunarModel {
+    fn period(&self) -> Duration {
+        match self {
+            LunarModel::Simple => Moon.period(),
+            LunarModel::Meeus => meeus::MeeusMoon.period(),
+        }
+    }
+
+    fn position(&self, time: &NaiveDateTime, location: &Location) -> SkyPosition {
+        match self {
+            LunarModel::Simple => Moon.position(time, location),
+            LunarModel::Meeus => meeus::MeeusMoon.position(time, location),
+        }
+    }
+
+    fn angular_radius(&self, time: &NaiveDateTime, location: &Location) -> Radians {
+        match self {
+            LunarModel::Simple => Moon.angular_radius(time, location),
+            LunarModel::Meeus => meeus::MeeusMoon.angular_radius(time, location),
+        }
+    }
+}
+
+impl PhysicalEphemeris for LunarModel {
+    type Ephemeris = MoonPhase;
 
-        SkyPosition { altitude, azimuth }
+    // The phase does not depend on the exact position, so both models share it
+    fn ephemeris(&self, time: &NaiveDateTime, location: &Location) -> MoonPhase {
+        Moon.ephemeris(time, location)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhaseName {
+    NewMoon,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    FullMoon,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl PhaseName {
+    /// The phase for the elongation of the moon from the sun in ecliptic longitude (in radians)
+    fn from_elongation(elongation: f64) -> Self {
+        let octant = ((elongation.normalize_radians() + PI / 8.) / (PI / 4.)).floor() as usize;
+        match octant % 8 {
+            0 => PhaseName::NewMoon,
+            1 => PhaseName::WaxingCrescent,
+            2 => PhaseName::FirstQuarter,
+            3 => PhaseName::WaxingGibbous,
+            4 => PhaseName::FullMoon,
+            5 => PhaseName::WaningGibbous,
+            6 => PhaseName::LastQuarter,
+            _ => PhaseName::WaningCrescent,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoonPhase {
+    pub phase: PhaseName,
+    /// Illuminated fraction of the disc, between 0 and 1
+    pub illuminated_fraction: f64,
+    /// Days since the last new moon
+    pub age: f64,
+    /// Position angle of the bright limb, measured from north towards east
+    pub position_angle: Radians,
+}
+
+impl PhysicalEphemeris for Moon {
+    type Ephemeris = MoonPhase;
+
+    // source: Meeus, Astronomical Algorithms, chapter 48
+    fn ephemeris(&self, time: &NaiveDateTime, _location: &Location) -> MoonPhase {
+        let GeocentricCoordinates {
+            mlon,
+            mlat,
+            r,
+            alpha,
+            delta,
+        } = geocentric_coordinates(time);
+        let sun::EquatorialCoordinates {
+            lambda: slon,
+            alpha: salpha,
+            delta: sdelta,
+        } = sun::equatorial_coordinates(time);
+
+        let elongation_lon = (mlon - slon).normalize_radians();
+        let elongation = (mlat.cos() * elongation_lon.cos()).acos();
+        let phase_angle =
+            (SUN_DISTANCE * elongation.sin()).atan2(r - SUN_DISTANCE * elongation.cos());
+
+        let position_angle = (sdelta.cos() * (salpha - alpha).sin())
+            .atan2(sdelta.sin() * delta.cos() - sdelta.cos() * delta.sin() * (salpha - alpha).cos())
+            .normalize_radians();
+
+        MoonPhase {
+            phase: PhaseName::from_elongation(elongation_lon),
+            illuminated_fraction: (1. + phase_angle.cos()) / 2.,
+            age: elongation_lon / (2. * PI) * SYNODIC_MONTH,
+            position_angle: Radians(position_angle),
+        }
+    }
+}
+
+struct GeocentricCoordinates {
+    /// Ecliptic longitude
+    mlon: f64,
+    /// Ecliptic latitude
+    mlat: f64,
+    /// Distance in earth radii
+    r: f64,
+    /// Right ascension
+    alpha: f64,
+    /// Declination
+    delta: f64,
+}
+
+// source: http://www.stjarnhimlen.se/comp/tutorial.html#7
+#[allow(non_snake_case)]
+fn geocentric_coordinates(time: &NaiveDateTime) -> GeocentricCoordinates {
+    let d = julian::day_of(time) - 2451543.5;
+
+    let N = (LONG_ASC_NODE_0 - LONG_ASC_NODE_1 * d).normalize_radians();
+    let w = (ARG_OF_PERIGEE_0 + ARG_OF_PERIGEE_1 * d).normalize_radians();
+    let M = (MEAN_ANOMALY_0 + MEAN_ANOMALY_1 * d).normalize_radians();
+
+    // Eccentric anomaly
+    let mut E0 = (M + ECCENTRICITY * M.sin() * (1. + ECCENTRICITY * M.cos())).normalize_radians();
+    loop {
+        let E1 = (E0 - (E0 - ECCENTRICITY * E0.sin() - M) / (1. - ECCENTRICITY * E0.cos()))
+            .normalize_radians();
+
+        let delta = (E0 - E1).abs();
+        E0 = E1;
+        if delta < ECCENTRICITY_EPS {
+            break;
+        }
+    }
+    let E = E0;
+
+    // Rectangular coordinates
+    let x = MEAN_DISTANCE * (E.cos() - ECCENTRICITY);
+    let y = MEAN_DISTANCE * (1. - ECCENTRICITY.powi(2)).sqrt() * E.sin();
+
+    // Distance and true anomaly
+    let r = (x.powi(2) + y.powi(2)).sqrt();
+    let v = y.atan2(x);
+
+    // Ecliptic coordinates
+    let vwsin = (v + w).sin();
+    let xeclip = r * (N.cos() * (v + w).cos() - N.sin() * vwsin * INCLINATION.cos());
+    let yeclip = r * (N.sin() * (v + w).cos() + N.cos() * vwsin * INCLINATION.cos());
+    let zeclip = r * vwsin * INCLINATION.sin();
+
+    let mut mlon = yeclip.atan2(xeclip);
+    let xeclip_yeclip_squared = xeclip.powi(2) + yeclip.powi(2);
+    let mut mlat = zeclip.atan2((xeclip_yeclip_squared).sqrt());
+    let mut r = (xeclip_yeclip_squared + zeclip.powi(2)).sqrt();
+
+    // Pertubations
+
+    let (Ls, Ms) = sun_mean_length_and_anomaly(d);
+    let Lm = N + w + M;
+    let Mm = M;
+    let D = Lm - Ls;
+    let F = Lm - N;
+
+    let d2 = 2. * d;
+    mlon += PERT_LON_0 * (M - d2).sin()
+        + PERT_LON_1 * d2.sin()
+        + PERT_LON_2 * Ms.sin()
+        + PERT_LON_3 * (2. * Mm - d2).sin()
+        + PERT_LON_4 * (Mm - d2 + Ms).sin()
+        + PERT_LON_5 * (Mm + d2).sin()
+        + PERT_LON_6 * (d2 - Ms).sin()
+        + PERT_LON_7 * (Mm - Ms).sin()
+        + PERT_LON_8 * D.sin()
+        + PERT_LON_9 * (Mm + Ms).sin()
+        + PERT_LON_10 * (2. * F - d2).sin()
+        + PERT_LON_11 * (Mm - 4. * D).sin();
+
+    mlat += PERT_LAT_0 * (F - d2).sin()
+        + PERT_LAT_1 * (Mm - F - d2).sin()
+        + PERT_LAT_2 * (Mm + F - d2).sin()
+        + PERT_LAT_3 * (F + d2).sin()
+        + PERT_LAT_4 * (2. * Mm + F).sin();
+
+    r += PERT_R_0 * (Mm - d2).cos() + PERT_R_1 * d2.cos();
+
+    // Equatorial coordinates
+    let oblecl = OBLIQUITY_ECLIPTIC_0 - OBLIQUITY_ECLIPTIC_1 * d;
+
+    let (mlon_sin, mlon_cos) = mlon.sin_cos();
+    let (mlat_sin, mlat_cos) = mlat.sin_cos();
+    let xeclip = mlon_cos * mlat_cos;
+    let yeclip = mlon_sin * mlat_cos;
+    let zeclip = mlat_sin;
+
+    let xequat = xeclip;
+    let (oblecl_sin, oblecl_cos) = oblecl.sin_cos();
+    let yequat = yeclip * oblecl_cos - zeclip * oblecl_sin;
+    let zequat = yeclip * oblecl_sin + zeclip * oblecl_cos;
+
+    let alpha = yequat.atan2(xequat);
+    let delta = zequat.atan2((xequat.powi(2) + yequat.powi(2)).sqrt());
+
+    GeocentricCoordinates {
+        mlon,
+        mlat,
+        r,
+        alpha,
+        delta,
     }
 }
 
@@ -188,11 +387,53 @@ mod test {
         let location = Location {
             lat: 48.1,
             lon: 11.6,
+            ..Default::default()
         };
 
         let pos = Moon.position(&time, &location);
 
-        assert_approx_eq(pos.altitude, -0.2933753239671207);
-        assert_approx_eq(pos.azimuth, 1.7483650904968921);
+        assert_approx_eq(pos.altitude.0, -0.2933753239671207);
+        assert_approx_eq(pos.azimuth.0, 1.7483650904968921);
+    }
+
+    #[test]
+    fn moon_phase_full() {
+        let time = NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(2024, 3, 25).unwrap(),
+            NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+        );
+
+        let location = Location {
+            lat: 48.1,
+            lon: 11.6,
+            ..Default::default()
+        };
+
+        let phase = Moon.ephemeris(&time, &location);
+
+        assert_eq!(phase.phase, PhaseName::FullMoon);
+        assert!(phase.illuminated_fraction > 0.99);
+        assert!((phase.age - SYNODIC_MONTH / 2.).abs() < 0.5);
+    }
+
+    #[test]
+    fn moon_phase_first_quarter() {
+        let time = NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(2024, 3, 17).unwrap(),
+            NaiveTime::from_hms_opt(4, 11, 0).unwrap(),
+        );
+
+        let location = Location {
+            lat: 48.1,
+            lon: 11.6,
+            ..Default::default()
+        };
+
+        let phase = Moon.ephemeris(&time, &location);
+
+        assert_eq!(phase.phase, PhaseName::FirstQuarter);
+        assert!((phase.illuminated_fraction - 0.5).abs() < 0.03);
+        // The bright limb of a waxing moon faces west
+        assert!(phase.position_angle > Radians(PI));
     }
 }
@@ -0,0 +1,361 @@
+use chrono::{Duration, NaiveDateTime};
+
+use crate::{
+    angle::{AngleExtensions, Radians},
+    julian,
+    sky::{util, SkyObject, SkyPosition},
+    Location,
+};
+
+const JDE_2000: f64 = 2451545.;
+const DAYS_PER_CENTURY: f64 = 36525.;
+
+const MEAN_DISTANCE: f64 = 385000.56; // km
+pub(crate) const EARTH_RADIUS: f64 = 6378.14; // km
+pub(crate) const MOON_RADIUS: f64 = 1737.4; // km
+
+/// Periodic terms for the longitude (`l`) and distance (`r`) of the moon, given by the multiples
+/// of the arguments (D, M, M', F) and the coefficients of `sin` (in 1e-6 degrees) and `cos`
+/// (in metres) respectively
+///
+/// source: Meeus, Astronomical Algorithms, table 47.A
+#[rustfmt::skip]
+const LR_TERMS: &[(i8, i8, i8, i8, f64, f64)] = &[
+    (0, 0, 1, 0, 6288774., -20905355.),
+    (2, 0, -1, 0, 1274027., -3699111.),
+    (2, 0, 0, 0, 658314., -2955968.),
+    (0, 0, 2, 0, 213618., -569925.),
+    (0, 1, 0, 0, -185116., 48888.),
+    (0, 0, 0, 2, -114332., -3149.),
+    (2, 0, -2, 0, 58793., 246158.),
+    (2, -1, -1, 0, 57066., -152138.),
+    (2, 0, 1, 0, 53322., -170733.),
+    (2, -1, 0, 0, 45758., -204586.),
+    (0, 1, -1, 0, -40923., -129620.),
+    (1, 0, 0, 0, -34720., 108743.),
+    (0, 1, 1, 0, -30383., 104755.),
+    (2, 0, 0, -2, 15327., 10321.),
+    (0, 0, 1, 2, -12528., 0.),
+    (0, 0, 1, -2, 10980., 79661.),
+    (4, 0, -1, 0, 10675., -34782.),
+    (0, 0, 3, 0, 10034., -23210.),
+    (4, 0, -2, 0, 8548., -21636.),
+    (2, 1, -1, 0, -7888., 24208.),
+    (2, 1, 0, 0, -6766., 30824.),
+    (1, 0, -1, 0, -5163., -8379.),
+    (1, 1, 0, 0, 4987., -16675.),
+    (2, -1, 1, 0, 4036., -12831.),
+    (2, 0, 2, 0, 3994., -10445.),
+    (4, 0, 0, 0, 3861., -11650.),
+    (2, 0, -3, 0, 3665., 14403.),
+    (0, 1, -2, 0, -2689., -7003.),
+    (2, 0, -1, 2, -2602., 0.),
+    (2, -1, -2, 0, 2390., 10056.),
+    (1, 0, 1, 0, -2348., 6322.),
+    (2, -2, 0, 0, 2236., -9884.),
+    (0, 1, 2, 0, -2120., 5751.),
+    (0, 2, 0, 0, -2069., 0.),
+    (2, -2, -1, 0, 2048., -4950.),
+    (2, 0, 1, -2, -1773., 4130.),
+    (2, 0, 0, 2, -1595., 0.),
+    (4, -1, -1, 0, 1215., -3958.),
+    (0, 0, 2, 2, -1110., 0.),
+    (3, 0, -1, 0, -892., 3258.),
+    (2, 1, 1, 0, -810., 2616.),
+    (4, -1, -2, 0, 759., -1897.),
+    (0, 2, -1, 0, -713., -2117.),
+    (2, 2, -1, 0, -700., 2354.),
+    (2, 1, -2, 0, 691., 0.),
+    (2, -1, 0, -2, 596., 0.),
+    (4, 0, 1, 0, 549., -1423.),
+    (0, 0, 4, 0, 537., -1117.),
+    (4, -1, 0, 0, 520., -1571.),
+    (1, 0, -2, 0, -487., -1739.),
+    (2, 1, 0, -2, -399., 0.),
+    (0, 0, 2, -2, -381., -4421.),
+    (1, 1, 1, 0, 351., 0.),
+    (3, 0, -2, 0, -340., 0.),
+    (4, 0, -3, 0, 330., 0.),
+    (2, -1, 2, 0, 327., 0.),
+    (0, 2, 1, 0, -323., 1165.),
+    (1, 1, -1, 0, 299., 0.),
+    (2, 0, 3, 0, 294., 0.),
+    (2, 0, -1, -2, 0., 8752.),
+];
+
+/// Periodic terms for the latitude (`b`) of the moon, given by the multiples of the arguments
+/// (D, M, M', F) and the coefficient of `sin` (in 1e-6 degrees)
+///
+/// source: Meeus, Astronomical Algorithms, table 47.B
+#[rustfmt::skip]
+const B_TERMS: &[(i8, i8, i8, i8, f64)] = &[
+    (0, 0, 0, 1, 5128122.),
+    (0, 0, 1, 1, 280602.),
+    (0, 0, 1, -1, 277693.),
+    (2, 0, 0, -1, 173237.),
+    (2, 0, -1, 1, 55413.),
+    (2, 0, -1, -1, 46271.),
+    (2, 0, 0, 1, 32573.),
+    (0, 0, 2, 1, 17198.),
+    (2, 0, 1, -1, 9266.),
+    (0, 0, 2, -1, 8822.),
+    (2, -1, 0, -1, 8216.),
+    (2, 0, -2, -1, 4324.),
+    (2, 0, 1, 1, 4200.),
+    (2, 1, 0, -1, -3359.),
+    (2, -1, -1, 1, 2463.),
+    (2, -1, 0, 1, 2211.),
+    (2, -1, -1, -1, 2065.),
+    (0, 1, -1, -1, -1870.),
+    (4, 0, -1, -1, 1828.),
+    (0, 1, 0, 1, -1794.),
+    (0, 0, 0, 3, -1749.),
+    (0, 1, -1, 1, -1565.),
+    (1, 0, 0, 1, -1491.),
+    (0, 1, 1, 1, -1475.),
+    (0, 1, 1, -1, -1410.),
+    (0, 1, 0, -1, -1344.),
+    (1, 0, 0, -1, -1335.),
+    (0, 0, 3, 1, 1107.),
+    (4, 0, 0, -1, 1021.),
+    (4, 0, -1, 1, 833.),
+    (0, 0, 1, -3, 777.),
+    (4, 0, -2, 1, 671.),
+    (2, 0, 0, -3, 607.),
+    (2, 0, 2, -1, 596.),
+    (2, -1, 1, -1, 491.),
+    (2, 0, -2, 1, -451.),
+    (0, 0, 3, -1, 439.),
+    (2, 0, 2, 1, 422.),
+    (2, 0, -3, -1, 421.),
+    (2, 1, -1, 1, -366.),
+    (2, 1, 0, 1, -351.),
+    (4, 0, 0, 1, 331.),
+    (2, -1, 1, 1, 315.),
+    (2, -2, 0, -1, 302.),
+    (0, 0, 1, 3, -283.),
+    (2, 1, 1, -1, -229.),
+    (1, 1, 0, -1, 223.),
+    (1, 1, 0, 1, 223.),
+    (0, 1, -2, -1, -220.),
+    (2, 1, -1, -1, -220.),
+    (1, 0, 1, 1, -185.),
+    (2, -1, -2, -1, 181.),
+    (0, 1, 2, 1, -177.),
+    (4, 0, -2, -1, 176.),
+    (4, -1, -1, -1, 166.),
+    (1, 0, 1, -1, -164.),
+    (4, 0, 1, -1, 132.),
+    (1, 0, -1, -1, -119.),
+    (4, -1, 0, -1, 115.),
+    (2, -2, 0, 1, 107.),
+];
+
+/// Evaluate a polynomial in `t` with the coefficients (in degrees) in ascending order
+fn polynomial(coefficients: &[f64], t: f64) -> f64 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0., |acc, c| acc * t + c)
+        .to_radians()
+        .normalize_radians()
+}
+
+/// Geocentric ecliptic longitude, latitude (both in radians) and distance (in km) of the moon,
+/// referred to the mean equinox of the date
+#[allow(non_snake_case)]
+fn geocentric_coordinates(t: f64) -> (f64, f64, f64) {
+    // Mean longitude, elongation, anomalies of the sun and the moon and argument of latitude
+    let L = polynomial(
+        &[
+            218.3164477,
+            481267.88123421,
+            -0.0015786,
+            1. / 538841.,
+            -1. / 65194000.,
+        ],
+        t,
+    );
+    let D = polynomial(
+        &[
+            297.8501921,
+            445267.1114034,
+            -0.0018819,
+            1. / 545868.,
+            -1. / 113065000.,
+        ],
+        t,
+    );
+    let M = polynomial(&[357.5291092, 35999.0502909, -0.0001536, 1. / 24490000.], t);
+    let Mm = polynomial(
+        &[
+            134.9633964,
+            477198.8675055,
+            0.0087414,
+            1. / 69699.,
+            -1. / 14712000.,
+        ],
+        t,
+    );
+    let F = polynomial(
+        &[
+            93.2720950,
+            483202.0175233,
+            -0.0036539,
+            -1. / 3526000.,
+            1. / 863310000.,
+        ],
+        t,
+    );
+
+    // Action of venus, jupiter and the flattening of the earth
+    let A1 = polynomial(&[119.75, 131.849], t);
+    let A2 = polynomial(&[53.09, 479264.290], t);
+    let A3 = polynomial(&[313.45, 481266.484], t);
+
+    // Decreasing eccentricity of the earth's orbit
+    let E = 1. - 0.002516 * t - 0.0000074 * t.powi(2);
+    let eccentricity = |m: i8| E.powi(m.abs().into());
+
+    let argument = |d: i8, m: i8, mm: i8, f: i8| {
+        f64::from(d) * D + f64::from(m) * M + f64::from(mm) * Mm + f64::from(f) * F
+    };
+
+    let (mut sum_l, sum_r) =
+        LR_TERMS
+            .iter()
+            .fold((0., 0.), |(sum_l, sum_r), &(d, m, mm, f, l, r)| {
+                let arg = argument(d, m, mm, f);
+                let e = eccentricity(m);
+                (sum_l + e * l * arg.sin(), sum_r + e * r * arg.cos())
+            });
+    let mut sum_b: f64 = B_TERMS
+        .iter()
+        .map(|&(d, m, mm, f, b)| eccentricity(m) * b * argument(d, m, mm, f).sin())
+        .sum();
+
+    sum_l += 3958. * A1.sin() + 1962. * (L - F).sin() + 318. * A2.sin();
+    sum_b += -2235. * L.sin()
+        + 382. * A3.sin()
+        + 175. * (A1 - F).sin()
+        + 175. * (A1 + F).sin()
+        + 127. * (L - Mm).sin()
+        - 115. * (L + Mm).sin();
+
+    let lambda = (L + (sum_l / 1e6).to_radians()).normalize_radians();
+    let beta = (sum_b / 1e6).to_radians();
+    let distance = MEAN_DISTANCE + sum_r / 1e3;
+
+    (lambda, beta, distance)
+}
+
+pub(crate) struct ApparentCoordinates {
+    /// Right ascension
+    pub alpha: f64,
+    /// Declination
+    pub delta: f64,
+    /// Distance in km
+    pub distance: f64,
+    /// Difference between apparent and mean sidereal time (in radians)
+    pub equation_of_equinoxes: f64,
+}
+
+/// Apparent geocentric coordinates of the moon at the given julian ephemeris day
+pub(crate) fn apparent_coordinates(jde: f64) -> ApparentCoordinates {
+    let t = (jde - JDE_2000) / DAYS_PER_CENTURY;
+
+    let (lambda, beta, distance) = geocentric_coordinates(t);
+
+    let (delta_psi, delta_epsilon) = util::nutation(t);
+    let epsilon = util::mean_obliquity(t) + delta_epsilon;
+
+    let lambda = (lambda + delta_psi).normalize_radians();
+
+    let (lambda_sin, lambda_cos) = lambda.sin_cos();
+    let (epsilon_sin, epsilon_cos) = epsilon.sin_cos();
+    let alpha = (lambda_sin * epsilon_cos - beta.tan() * epsilon_sin)
+        .atan2(lambda_cos)
+        .normalize_radians();
+    let delta = (beta.sin() * epsilon_cos + beta.cos() * epsilon_sin * lambda_sin).asin();
+
+    ApparentCoordinates {
+        alpha,
+        delta,
+        distance,
+        equation_of_equinoxes: delta_psi * epsilon_cos,
+    }
+}
+
+/// High precision model of the moon, based on the main terms of the ELP-2000/82 theory
+/// and corrected for the topocentric parallax of the observer
+pub struct MeeusMoon;
+impl SkyObject for MeeusMoon {
+    fn period(&self) -> Duration {
+        Duration::try_hours(26).expect("constant value")
+    }
+
+    // source: Meeus, Astronomical Algorithms, chapter 47
+    fn position(&self, time: &NaiveDateTime, location: &Location) -> SkyPosition {
+        let jde = julian::ephemeris_day_of(time);
+        let ApparentCoordinates {
+            alpha,
+            delta,
+            distance,
+            equation_of_equinoxes,
+        } = apparent_coordinates(jde);
+
+        // The conversion uses the mean sidereal time, shifting the right ascension
+        // by the equation of the equinoxes yields the apparent hour angle
+        let alpha = alpha - equation_of_equinoxes;
+
+        // Correct for parallax
+        let parallax = (EARTH_RADIUS / distance).asin();
+        let hour_angle = util::sidereal_time(time, location, alpha);
+        let (alpha, delta) = util::topocentric_equatorial(
+            alpha,
+            delta,
+            hour_angle,
+            parallax,
+            location.lat.to_radians(),
+            location.elevation,
+        );
+
+        let (altitude, azimuth) =
+            util::convert_ecliptic_to_horizontal(time, location, alpha, delta);
+        let altitude = util::refraction(altitude, location);
+
+        SkyPosition {
+            altitude: Radians(altitude),
+            azimuth: Radians(azimuth),
+        }
+    }
+
+    fn angular_radius(&self, time: &NaiveDateTime, _location: &Location) -> Radians {
+        let t = (julian::ephemeris_day_of(time) - JDE_2000) / DAYS_PER_CENTURY;
+        let (_, _, distance) = geocentric_coordinates(t);
+
+        Radians((MOON_RADIUS / distance).asin())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::assert_degrees_eq;
+
+    // source: Meeus, Astronomical Algorithms, example 47.a
+    #[test]
+    fn moon_coordinates_meeus() {
+        let jde = 2448724.5;
+        let t = (jde - JDE_2000) / DAYS_PER_CENTURY;
+
+        let (lambda, beta, distance) = geocentric_coordinates(t);
+        assert_degrees_eq(Radians(lambda), 133.162_655, 1e-5);
+        assert_degrees_eq(Radians(beta), -3.229_126, 1e-5);
+        assert!((distance - 368_409.7).abs() < 0.1);
+
+        let ApparentCoordinates { alpha, delta, .. } = apparent_coordinates(jde);
+        assert_degrees_eq(Radians(alpha), 134.688_470, 2e-4);
+        assert_degrees_eq(Radians(delta), 13.768_368, 2e-4);
+    }
+}
@@ -0,0 +1,261 @@
+use std::f64::consts::PI;
+
+use chrono::{Duration, NaiveDateTime};
+
+use crate::{
+    angle::{AngleExtensions, Radians},
+    julian, Location, SkyObject, SkyPosition,
+};
+
+use super::util;
+
+const ECCENTRICITY_EPS: f64 = 1e-8;
+
+const OBLIQUITY_ECLIPTIC_0: f64 = 23.4393 * PI / 180.;
+const OBLIQUITY_ECLIPTIC_1: f64 = 3.563e-7 * PI / 180.;
+
+/// An orbital element given by its value at the epoch and its change per day (in degrees)
+struct Element(f64, f64);
+
+impl Element {
+    fn at(&self, d: f64) -> f64 {
+        self.0 + self.1 * d
+    }
+
+    fn radians_at(&self, d: f64) -> f64 {
+        self.at(d).to_radians().normalize_radians()
+    }
+}
+
+struct OrbitalElements {
+    long_asc_node: Element,
+    inclination: Element,
+    arg_of_perihelion: Element,
+    /// In astronomical units
+    mean_distance: f64,
+    eccentricity: Element,
+    mean_anomaly: Element,
+}
+
+// source: http://www.stjarnhimlen.se/comp/tutorial.html#4
+const SUN: OrbitalElements = OrbitalElements {
+    long_asc_node: Element(0., 0.),
+    inclination: Element(0., 0.),
+    arg_of_perihelion: Element(282.9404, 4.70935e-5),
+    mean_distance: 1.,
+    eccentricity: Element(0.016709, -1.151e-9),
+    mean_anomaly: Element(356.0470, 0.9856002585),
+};
+
+const MERCURY: OrbitalElements = OrbitalElements {
+    long_asc_node: Element(48.3313, 3.24587e-5),
+    inclination: Element(7.0047, 5.00e-8),
+    arg_of_perihelion: Element(29.1241, 1.01444e-5),
+    mean_distance: 0.387098,
+    eccentricity: Element(0.205635, 5.59e-10),
+    mean_anomaly: Element(168.6562, 4.0923344368),
+};
+
+const VENUS: OrbitalElements = OrbitalElements {
+    long_asc_node: Element(76.6799, 2.46590e-5),
+    inclination: Element(3.3946, 2.75e-8),
+    arg_of_perihelion: Element(54.8910, 1.38374e-5),
+    mean_distance: 0.723330,
+    eccentricity: Element(0.006773, -1.302e-9),
+    mean_anomaly: Element(48.0052, 1.6021302244),
+};
+
+const MARS: OrbitalElements = OrbitalElements {
+    long_asc_node: Element(49.5574, 2.11081e-5),
+    inclination: Element(1.8497, -1.78e-8),
+    arg_of_perihelion: Element(286.5016, 2.92961e-5),
+    mean_distance: 1.523688,
+    eccentricity: Element(0.093405, 2.516e-9),
+    mean_anomaly: Element(18.6021, 0.5240207766),
+};
+
+const JUPITER: OrbitalElements = OrbitalElements {
+    long_asc_node: Element(100.4542, 2.76854e-5),
+    inclination: Element(1.3030, -1.557e-7),
+    arg_of_perihelion: Element(273.8777, 1.64505e-5),
+    mean_distance: 5.20256,
+    eccentricity: Element(0.048498, 4.469e-9),
+    mean_anomaly: Element(19.8950, 0.0830853001),
+};
+
+const SATURN: OrbitalElements = OrbitalElements {
+    long_asc_node: Element(113.6634, 2.38980e-5),
+    inclination: Element(2.4886, -1.081e-7),
+    arg_of_perihelion: Element(339.3939, 2.97661e-5),
+    mean_distance: 9.55475,
+    eccentricity: Element(0.055546, -9.499e-9),
+    mean_anomaly: Element(316.9670, 0.0334442282),
+};
+
+impl OrbitalElements {
+    /// Heliocentric ecliptic longitude, latitude and distance (in AU)
+    #[allow(non_snake_case)]
+    fn ecliptic_coordinates(&self, d: f64) -> (f64, f64, f64) {
+        let N = self.long_asc_node.radians_at(d);
+        let i = self.inclination.radians_at(d);
+        let w = self.arg_of_perihelion.radians_at(d);
+        let a = self.mean_distance;
+        let e = self.eccentricity.at(d);
+        let M = self.mean_anomaly.radians_at(d);
+
+        // Eccentric anomaly
+        let mut E = M + e * M.sin() * (1. + e * M.cos());
+        loop {
+            let E1 = E - (E - e * E.sin() - M) / (1. - e * E.cos());
+            let delta = (E - E1).abs();
+            E = E1;
+            if delta < ECCENTRICITY_EPS {
+                break;
+            }
+        }
+
+        // Distance and true anomaly
+        let xv = a * (E.cos() - e);
+        let yv = a * (1. - e.powi(2)).sqrt() * E.sin();
+        let v = yv.atan2(xv);
+        let r = (xv.powi(2) + yv.powi(2)).sqrt();
+
+        // Position in space
+        let (vw_sin, vw_cos) = (v + w).sin_cos();
+        let xh = r * (N.cos() * vw_cos - N.sin() * vw_sin * i.cos());
+        let yh = r * (N.sin() * vw_cos + N.cos() * vw_sin * i.cos());
+        let zh = r * vw_sin * i.sin();
+
+        let lon = yh.atan2(xh);
+        let lat = zh.atan2((xh.powi(2) + yh.powi(2)).sqrt());
+
+        (lon, lat, r)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Planet {
+    Mercury,
+    Venus,
+    Mars,
+    Jupiter,
+    Saturn,
+}
+
+impl Planet {
+    fn elements(&self) -> &'static OrbitalElements {
+        match self {
+            Planet::Mercury => &MERCURY,
+            Planet::Venus => &VENUS,
+            Planet::Mars => &MARS,
+            Planet::Jupiter => &JUPITER,
+            Planet::Saturn => &SATURN,
+        }
+    }
+
+    /// Heliocentric ecliptic longitude, latitude and distance (in AU),
+    /// including the perturbations between Jupiter and Saturn
+    #[allow(non_snake_case)]
+    fn ecliptic_coordinates(&self, d: f64) -> (f64, f64, f64) {
+        let (mut lon, mut lat, r) = self.elements().ecliptic_coordinates(d);
+
+        let Mj = JUPITER.mean_anomaly.at(d);
+        let Ms = SATURN.mean_anomaly.at(d);
+        let sin = |deg: f64| deg.to_radians().sin();
+        let cos = |deg: f64| deg.to_radians().cos();
+
+        match self {
+            Planet::Jupiter => {
+                lon += (-0.332 * sin(2. * Mj - 5. * Ms - 67.6)
+                    - 0.056 * sin(2. * Mj - 2. * Ms + 21.)
+                    + 0.042 * sin(3. * Mj - 5. * Ms + 21.)
+                    - 0.036 * sin(Mj - 2. * Ms)
+                    + 0.022 * cos(Mj - Ms)
+                    + 0.023 * sin(2. * Mj - 3. * Ms + 52.)
+                    - 0.016 * sin(Mj - 5. * Ms - 69.))
+                .to_radians();
+            }
+            Planet::Saturn => {
+                lon += (0.812 * sin(2. * Mj - 5. * Ms - 67.6)
+                    - 0.229 * cos(2. * Mj - 4. * Ms - 2.)
+                    + 0.119 * sin(Mj - 2. * Ms - 3.)
+                    + 0.046 * sin(2. * Mj - 6. * Ms - 69.)
+                    + 0.014 * sin(Mj - 3. * Ms + 32.))
+                .to_radians();
+                lat += (-0.020 * cos(2. * Mj - 4. * Ms - 2.)
+                    + 0.018 * sin(2. * Mj - 6. * Ms - 49.))
+                .to_radians();
+            }
+            _ => (),
+        }
+
+        (lon, lat, r)
+    }
+
+    /// Geocentric right ascension and declination
+    fn equatorial_coordinates(&self, time: &NaiveDateTime) -> (f64, f64) {
+        let d = julian::day_of(time) - 2451543.5;
+
+        let (lon, lat, r) = self.ecliptic_coordinates(d);
+        let (slon, _, rs) = SUN.ecliptic_coordinates(d);
+
+        // Geocentric ecliptic coordinates, the sun's position is the negated position of earth
+        let xg = r * lon.cos() * lat.cos() + rs * slon.cos();
+        let yg = r * lon.sin() * lat.cos() + rs * slon.sin();
+        let zg = r * lat.sin();
+
+        // Equatorial coordinates
+        let oblecl = OBLIQUITY_ECLIPTIC_0 - OBLIQUITY_ECLIPTIC_1 * d;
+        let (oblecl_sin, oblecl_cos) = oblecl.sin_cos();
+        let xe = xg;
+        let ye = yg * oblecl_cos - zg * oblecl_sin;
+        let ze = yg * oblecl_sin + zg * oblecl_cos;
+
+        let alpha = ye.atan2(xe).normalize_radians();
+        let delta = ze.atan2((xe.powi(2) + ye.powi(2)).sqrt());
+
+        (alpha, delta)
+    }
+}
+
+impl SkyObject for Planet {
+    fn period(&self) -> Duration {
+        Duration::try_days(1).expect("constant value")
+    }
+
+    // source: http://www.stjarnhimlen.se/comp/tutorial.html
+    fn position(&self, time: &NaiveDateTime, location: &Location) -> SkyPosition {
+        let (alpha, delta) = self.equatorial_coordinates(time);
+
+        let (altitude, azimuth) =
+            util::convert_ecliptic_to_horizontal(time, location, alpha, delta);
+        let altitude = util::refraction(altitude, location);
+
+        SkyPosition {
+            altitude: Radians(altitude),
+            azimuth: Radians(azimuth),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{NaiveDate, NaiveTime};
+
+    use super::*;
+    use crate::util::assert_degrees_eq;
+
+    // source: Meeus, Astronomical Algorithms, example 33.a
+    #[test]
+    fn venus_position_meeus() {
+        let time = NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(1992, 12, 20).unwrap(),
+            NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+        );
+
+        let (alpha, delta) = Planet::Venus.equatorial_coordinates(&time);
+
+        assert_degrees_eq(Radians(alpha), 316.172_725, 0.05);
+        assert_degrees_eq(Radians(delta), -18.888_011, 0.05);
+    }
+}
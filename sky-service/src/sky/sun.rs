@@ -1,9 +1,16 @@
-use std::f64::consts::PI;
+use std::{env, f64::consts::PI, str::FromStr};
 
+use anyhow::anyhow;
 use chrono::{Duration, NaiveDateTime};
+use log::warn;
+use serde::{Deserialize, Serialize};
 
 use super::{util, SkyObject, SkyPosition};
-use crate::{julian, location::Location};
+use crate::{angle::Radians, julian, location::Location};
+
+pub mod vsop87;
+
+const SOLAR_MODEL_VAR: &str = "SOLAR_MODEL";
 
 const MEAN_ECLIPTIC_LENGTH_C0: f64 = 280.460f64 * PI / 180.;
 const MEAN_ECLIPTIC_LENGTH_C1: f64 = 0.9856474f64 * PI / 180.;
@@ -14,6 +21,9 @@ const ECLIPTIC_LENGTH_C1: f64 = 0.01997 * PI / 180.;
 const SKEW_OF_ECLIPTIC_C0: f64 = 23.439 * PI / 180.;
 const SKEW_OF_ECLIPTIC_C1: f64 = 0.4e-6 * PI / 180.;
 
+/// Mean apparent radius of the disc of the sun
+const ANGULAR_RADIUS: f64 = 0.2666 * PI / 180.;
+
 pub struct Sun;
 impl SkyObject for Sun {
     fn period(&self) -> Duration {
@@ -22,27 +32,115 @@ impl SkyObject for Sun {
 
     // source: https://de.wikipedia.org/wiki/Sonnenstand
     fn position(&self, time: &NaiveDateTime, location: &Location) -> SkyPosition {
-        // Ecliptic coordinates
-        let n = julian::day_of_since_2000(time);
+        let EquatorialCoordinates { alpha, delta, .. } = equatorial_coordinates(time);
+
+        let (altitude, azimuth) =
+            util::convert_ecliptic_to_horizontal(time, location, alpha, delta);
+        let altitude = util::refraction(altitude, location);
 
-        let l = MEAN_ECLIPTIC_LENGTH_C0 + MEAN_ECLIPTIC_LENGTH_C1 * n;
-        let g = MEAN_ECLIPTIC_ANOMALY_C0 + MEAN_ECLIPTIC_ANOMALY_C1 * n;
-        let lambda = l + ECLIPTIC_LENGTH_C0 * g.sin() + ECLIPTIC_LENGTH_C1 * (2. * g).sin();
+        SkyPosition {
+            altitude: Radians(altitude),
+            azimuth: Radians(azimuth),
+        }
+    }
 
-        let epsilon = SKEW_OF_ECLIPTIC_C0 + n * SKEW_OF_ECLIPTIC_C1;
+    fn angular_radius(&self, _time: &NaiveDateTime, _location: &Location) -> Radians {
+        Radians(ANGULAR_RADIUS)
+    }
+}
+
+/// The model used for calculating the position of the sun
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SolarModel {
+    /// Short formula, see [`Sun`]
+    #[default]
+    Simple,
+    /// Truncated VSOP87 theory, see [`vsop87::Vsop87Sun`]
+    Vsop87,
+}
+
+impl SolarModel {
+    /// The model configured by the environment variable `SOLAR_MODEL`,
+    /// falls back to the default model if it is not set or invalid
+    pub fn from_env() -> Self {
+        env::var(SOLAR_MODEL_VAR)
+            .ok()
+            .and_then(|value| {
+                value
+                    .parse()
+                    .inspect_err(|err| warn!("Ignoring {SOLAR_MODEL_VAR}: {err}"))
+                    .ok()
+            })
+            .unwrap_or_default()
+    }
+}
 
-        // Equatorial coordinates
-        let mut alpha = (epsilon.cos() * lambda.tan()).atan();
-        if lambda.cos() < 0. {
-            alpha += PI;
+impl FromStr for SolarModel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "simple" => Ok(SolarModel::Simple),
+            "vsop87" => Ok(SolarModel::Vsop87),
+            _ => Err(anyhow!("unknown solar model '{s}'")),
         }
-        let delta = (epsilon.sin() * lambda.sin()).asin();
+    }
+}
 
-        let (altitude, azimuth) =
-            util::convert_ecliptic_to_horizontal(time, location, alpha, delta);
-        let altitude = util::refraction(altitude);
+impl SkyObject for SolarModel {
+    fn period(&self) -> Duration {
+        match self {
+            SolarModel::Simple => Sun.period(),
+            SolarModel::Vsop87 => vsop87::Vsop87Sun.period(),
+        }
+    }
 
-        SkyPosition { altitude, azimuth }
+    fn position(&self, time: &NaiveDateTime, location: &Location) -> SkyPosition {
+        match self {
+            SolarModel::Simple => Sun.position(time, location),
+            SolarModel::Vsop87 => vsop87::Vsop87Sun.position(time, location),
+        }
+    }
+
+    fn angular_radius(&self, time: &NaiveDateTime, location: &Location) -> Radians {
+        match self {
+            SolarModel::Simple => Sun.angular_radius(time, location),
+            SolarModel::Vsop87 => vsop87::Vsop87Sun.angular_radius(time, location),
+        }
+    }
+}
+
+pub(crate) struct EquatorialCoordinates {
+    /// Ecliptic longitude
+    pub lambda: f64,
+    /// Right ascension
+    pub alpha: f64,
+    /// Declination
+    pub delta: f64,
+}
+
+pub(crate) fn equatorial_coordinates(time: &NaiveDateTime) -> EquatorialCoordinates {
+    // Ecliptic coordinates
+    let n = julian::day_of_since_2000(time);
+
+    let l = MEAN_ECLIPTIC_LENGTH_C0 + MEAN_ECLIPTIC_LENGTH_C1 * n;
+    let g = MEAN_ECLIPTIC_ANOMALY_C0 + MEAN_ECLIPTIC_ANOMALY_C1 * n;
+    let lambda = l + ECLIPTIC_LENGTH_C0 * g.sin() + ECLIPTIC_LENGTH_C1 * (2. * g).sin();
+
+    let epsilon = SKEW_OF_ECLIPTIC_C0 + n * SKEW_OF_ECLIPTIC_C1;
+
+    // Equatorial coordinates
+    let mut alpha = (epsilon.cos() * lambda.tan()).atan();
+    if lambda.cos() < 0. {
+        alpha += PI;
+    }
+    let delta = (epsilon.sin() * lambda.sin()).asin();
+
+    EquatorialCoordinates {
+        lambda,
+        alpha,
+        delta,
     }
 }
 
@@ -52,7 +150,28 @@ mod test {
 
     use super::*;
     use crate::location::Location;
-    use crate::util::assert_approx_eq;
+    use crate::util::{assert_approx_eq, assert_degrees_eq};
+
+    // source: NREL solar position algorithm, example in appendix A.5
+    fn spa_reference() -> (NaiveDateTime, Location, f64, f64) {
+        let time = NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(2003, 10, 17).unwrap(),
+            NaiveTime::from_hms_opt(19, 30, 30).unwrap(),
+        );
+
+        let location = Location {
+            lat: 39.742476,
+            lon: -105.1786,
+            elevation: 1830.14,
+            pressure: Some(820.),
+            temperature: Some(11.),
+        };
+
+        let altitude = 90. - 50.11162;
+        let azimuth = 194.34024;
+
+        (time, location, altitude, azimuth)
+    }
 
     #[test]
     fn sun_position_wiki() {
@@ -64,12 +183,13 @@ mod test {
         let location = Location {
             lat: 48.1,
             lon: 11.6,
+            ..Default::default()
         };
 
         let pos = Sun.position(&time, &location);
 
-        assert_approx_eq(pos.altitude, 19.11f64.to_radians());
-        assert_approx_eq(pos.azimuth, 265.938f64.to_radians() - PI);
+        assert_approx_eq(pos.altitude.0, 19.11f64.to_radians());
+        assert_approx_eq(pos.azimuth.0, 265.938f64.to_radians() - PI);
     }
 
     #[test]
@@ -82,11 +202,38 @@ mod test {
         let location = Location {
             lat: 48.8187132,
             lon: 9.5878127,
+            ..Default::default()
         };
 
         let pos = Sun.position(&time, &location);
 
-        assert_approx_eq(pos.altitude, 0.00902);
-        assert_approx_eq(pos.azimuth, 1.19716 + PI);
+        assert_approx_eq(pos.altitude.0, 0.00902);
+        assert_approx_eq(pos.azimuth.0, 1.19716 + PI);
+    }
+
+    #[test]
+    fn simple_model_spa_reference() {
+        let (time, location, altitude, azimuth) = spa_reference();
+
+        let SkyPosition {
+            altitude: is_altitude,
+            azimuth: is_azimuth,
+        } = SolarModel::Simple.position(&time, &location);
+
+        assert_degrees_eq(is_altitude, altitude, 0.01);
+        assert_degrees_eq(is_azimuth, azimuth, 0.01);
+    }
+
+    #[test]
+    fn vsop87_model_spa_reference() {
+        let (time, location, altitude, azimuth) = spa_reference();
+
+        let SkyPosition {
+            altitude: is_altitude,
+            azimuth: is_azimuth,
+        } = SolarModel::Vsop87.position(&time, &location);
+
+        assert_degrees_eq(is_altitude, altitude, 0.0005);
+        assert_degrees_eq(is_azimuth, azimuth, 0.0005);
     }
 }
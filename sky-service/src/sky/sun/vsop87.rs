@@ -0,0 +1,451 @@
+// The tables contain rounded multiples of PI, which are kept as given
+#![allow(clippy::approx_constant)]
+
+use std::f64::consts::PI;
+
+use chrono::{Duration, NaiveDateTime};
+
+use crate::{
+    angle::{AngleExtensions, Radians},
+    julian,
+    sky::{
+        util::{self, ARCSECONDS},
+        SkyObject, SkyPosition,
+    },
+    Location,
+};
+
+const JDE_2000: f64 = 2451545.;
+const DAYS_PER_MILLENNIUM: f64 = 365250.;
+
+const ABERRATION: f64 = -20.4898 * ARCSECONDS;
+const SUN_PARALLAX: f64 = 8.794 * ARCSECONDS;
+/// Apparent radius of the disc of the sun at a distance of 1 AU
+pub(crate) const SUN_SEMIDIAMETER: f64 = 959.63 * ARCSECONDS;
+
+/// Periodic terms (A, B, C) of the VSOP87 theory for the earth, each evaluating to
+/// `A * cos(B + C * tau)`, truncated as in the NREL solar position algorithm
+type Terms = &'static [(f64, f64, f64)];
+
+const L0: Terms = &[
+    (175347046., 0., 0.),
+    (3341656., 4.6692568, 6283.07585),
+    (34894., 4.6261, 12566.1517),
+    (3497., 2.7441, 5753.3849),
+    (3418., 2.8289, 3.5231),
+    (3136., 3.6277, 77713.7715),
+    (2676., 4.4181, 7860.4194),
+    (2343., 6.1352, 3930.2097),
+    (1324., 0.7425, 11506.7698),
+    (1273., 2.0371, 529.691),
+    (1199., 1.1096, 1577.3435),
+    (990., 5.233, 5884.927),
+    (902., 2.045, 26.298),
+    (857., 3.508, 398.149),
+    (780., 1.179, 5223.694),
+    (753., 2.533, 5507.553),
+    (505., 4.583, 18849.228),
+    (492., 4.205, 775.523),
+    (357., 2.92, 0.067),
+    (317., 5.849, 11790.629),
+    (284., 1.899, 796.298),
+    (271., 0.315, 10977.079),
+    (243., 0.345, 5486.778),
+    (206., 4.806, 2544.314),
+    (205., 1.869, 5573.143),
+    (202., 2.458, 6069.777),
+    (156., 0.833, 213.299),
+    (132., 3.411, 2942.463),
+    (126., 1.083, 20.775),
+    (115., 0.645, 0.98),
+    (103., 0.636, 4694.003),
+    (102., 0.976, 15720.839),
+    (102., 4.267, 7.114),
+    (99., 6.21, 2146.17),
+    (98., 0.68, 155.42),
+    (86., 5.98, 161000.69),
+    (85., 1.3, 6275.96),
+    (85., 3.67, 71430.7),
+    (80., 1.81, 17260.15),
+    (79., 3.04, 12036.46),
+    (75., 1.76, 5088.63),
+    (74., 3.5, 3154.69),
+    (74., 4.68, 801.82),
+    (70., 0.83, 9437.76),
+    (62., 3.98, 8827.39),
+    (61., 1.82, 7084.9),
+    (57., 2.78, 6286.6),
+    (56., 4.39, 14143.5),
+    (56., 3.47, 6279.55),
+    (52., 0.19, 12139.55),
+    (52., 1.33, 1748.02),
+    (51., 0.28, 5856.48),
+    (49., 0.49, 1194.45),
+    (41., 5.37, 8429.24),
+    (41., 2.4, 19651.05),
+    (39., 6.17, 10447.39),
+    (37., 6.04, 10213.29),
+    (37., 2.57, 1059.38),
+    (36., 1.71, 2352.87),
+    (36., 1.78, 6812.77),
+    (33., 0.59, 17789.85),
+    (30., 0.44, 83996.85),
+    (30., 2.74, 1349.87),
+    (25., 3.16, 4690.48),
+];
+
+const L1: Terms = &[
+    (628331966747., 0., 0.),
+    (206059., 2.678235, 6283.07585),
+    (4303., 2.6351, 12566.1517),
+    (425., 1.59, 3.523),
+    (119., 5.796, 26.298),
+    (109., 2.966, 1577.344),
+    (93., 2.59, 18849.23),
+    (72., 1.14, 529.69),
+    (68., 1.87, 398.15),
+    (67., 4.41, 5507.55),
+    (59., 2.89, 5223.69),
+    (56., 2.17, 155.42),
+    (45., 0.4, 796.3),
+    (36., 0.47, 775.52),
+    (29., 2.65, 7.11),
+    (21., 5.34, 0.98),
+    (19., 1.85, 5486.78),
+    (19., 4.97, 213.3),
+    (17., 2.99, 6275.96),
+    (16., 0.03, 2544.31),
+    (16., 1.43, 2146.17),
+    (15., 1.21, 10977.08),
+    (12., 2.83, 1748.02),
+    (12., 3.26, 5088.63),
+    (12., 5.27, 1194.45),
+    (12., 2.08, 4694.),
+    (11., 0.77, 553.57),
+    (10., 1.3, 6286.6),
+    (10., 4.24, 1349.87),
+    (9., 2.7, 242.73),
+    (9., 5.64, 951.72),
+    (8., 5.3, 2352.87),
+    (6., 2.65, 9437.76),
+    (6., 4.67, 4690.48),
+];
+
+const L2: Terms = &[
+    (52919., 0., 0.),
+    (8720., 1.0721, 6283.0758),
+    (309., 0.867, 12566.152),
+    (27., 0.05, 3.52),
+    (16., 5.19, 26.3),
+    (16., 3.68, 155.42),
+    (10., 0.76, 18849.23),
+    (9., 2.06, 77713.77),
+    (7., 0.83, 775.52),
+    (5., 4.66, 1577.34),
+    (4., 1.03, 7.11),
+    (4., 3.44, 5573.14),
+    (3., 5.14, 796.3),
+    (3., 6.05, 5507.55),
+    (3., 1.19, 242.73),
+    (3., 6.12, 529.69),
+    (3., 0.31, 398.15),
+    (3., 2.28, 553.57),
+    (2., 4.38, 5223.69),
+    (2., 3.75, 0.98),
+];
+
+const L3: Terms = &[
+    (289., 5.844, 6283.076),
+    (35., 0., 0.),
+    (17., 5.49, 12566.15),
+    (3., 5.2, 155.42),
+    (1., 4.72, 3.52),
+    (1., 5.3, 18849.23),
+    (1., 5.97, 242.73),
+];
+
+const L4: Terms = &[(114., 3.142, 0.), (8., 4.13, 6283.08), (1., 3.84, 12566.15)];
+
+const L5: Terms = &[(1., 3.14, 0.)];
+
+const B0: Terms = &[
+    (280., 3.199, 84334.662),
+    (102., 5.422, 5507.553),
+    (80., 3.88, 5223.69),
+    (44., 3.7, 2352.87),
+    (32., 4., 1577.34),
+];
+
+const B1: Terms = &[(9., 3.9, 5507.55), (6., 1.73, 5223.69)];
+
+const R0: Terms = &[
+    (100013989., 0., 0.),
+    (1670700., 3.0984635, 6283.07585),
+    (13956., 3.05525, 12566.1517),
+    (3084., 5.1985, 77713.7715),
+    (1628., 1.1739, 5753.3849),
+    (1576., 2.8469, 7860.4194),
+    (925., 5.453, 11506.77),
+    (542., 4.564, 3930.21),
+    (472., 3.661, 5884.927),
+    (346., 0.964, 5507.553),
+    (329., 5.9, 5223.694),
+    (307., 0.299, 5573.143),
+    (243., 4.273, 11790.629),
+    (212., 5.847, 1577.344),
+    (186., 5.022, 10977.079),
+    (175., 3.012, 18849.228),
+    (110., 5.055, 5486.778),
+    (98., 0.89, 6069.78),
+    (86., 5.69, 15720.84),
+    (86., 1.27, 161000.69),
+    (65., 0.27, 17260.15),
+    (63., 0.92, 529.69),
+    (57., 2.01, 83996.85),
+    (56., 5.24, 71430.7),
+    (49., 3.25, 2544.31),
+    (47., 2.58, 775.52),
+    (45., 5.54, 9437.76),
+    (43., 6.01, 6275.96),
+    (39., 5.36, 4694.),
+    (38., 2.39, 8827.39),
+    (37., 0.83, 19651.05),
+    (37., 4.9, 12139.55),
+    (36., 1.67, 12036.46),
+    (35., 1.84, 2942.46),
+    (33., 0.24, 7084.9),
+    (32., 0.18, 5088.63),
+    (32., 1.78, 398.15),
+    (28., 1.21, 6286.6),
+    (28., 1.9, 6279.55),
+    (26., 4.59, 10447.39),
+];
+
+const R1: Terms = &[
+    (103019., 1.10749, 6283.07585),
+    (1721., 1.0644, 12566.1517),
+    (702., 3.142, 0.),
+    (32., 1.02, 18849.23),
+    (31., 2.84, 5507.55),
+    (25., 1.32, 5223.69),
+    (18., 1.42, 1577.34),
+    (10., 5.91, 10977.08),
+    (9., 1.42, 6275.96),
+    (9., 0.27, 5486.78),
+];
+
+const R2: Terms = &[
+    (4359., 5.7846, 6283.0758),
+    (124., 5.579, 12566.152),
+    (12., 3.14, 0.),
+    (9., 3.63, 77713.77),
+    (6., 1.87, 5573.14),
+    (3., 5.47, 18849.23),
+];
+
+const R3: Terms = &[(145., 4.273, 6283.076), (7., 3.92, 12566.15)];
+
+const R4: Terms = &[(4., 2.56, 6283.08)];
+
+const L_SERIES: &[Terms] = &[L0, L1, L2, L3, L4, L5];
+const B_SERIES: &[Terms] = &[B0, B1];
+const R_SERIES: &[Terms] = &[R0, R1, R2, R3, R4];
+
+/// Evaluate a series of VSOP87 terms as a polynomial in `tau`
+fn evaluate(series: &[Terms], tau: f64) -> f64 {
+    series.iter().rev().fold(0., |acc, terms| {
+        let sum: f64 = terms.iter().map(|(a, b, c)| a * (b + c * tau).cos()).sum();
+        acc * tau + sum
+    }) / 1e8
+}
+
+/// Heliocentric ecliptic longitude, latitude (both in radians) and distance (in AU) of the earth
+fn earth_heliocentric(jde: f64) -> (f64, f64, f64) {
+    let tau = (jde - JDE_2000) / DAYS_PER_MILLENNIUM;
+
+    let l = evaluate(L_SERIES, tau).normalize_radians();
+    let b = evaluate(B_SERIES, tau);
+    let r = evaluate(R_SERIES, tau);
+
+    (l, b, r)
+}
+
+pub(crate) struct ApparentCoordinates {
+    /// Right ascension
+    pub alpha: f64,
+    /// Declination
+    pub delta: f64,
+    /// Distance in AU
+    pub r: f64,
+    /// Difference between apparent and mean sidereal time (in radians)
+    pub equation_of_equinoxes: f64,
+}
+
+/// Apparent geocentric coordinates of the sun at the given julian ephemeris day
+pub(crate) fn apparent_coordinates(jde: f64) -> ApparentCoordinates {
+    let t = (jde - JDE_2000) / (DAYS_PER_MILLENNIUM / 10.);
+
+    let (l, b, r) = earth_heliocentric(jde);
+
+    // Geocentric coordinates
+    let theta = (l + PI).normalize_radians();
+    let beta = -b;
+
+    let (delta_psi, delta_epsilon) = util::nutation(t);
+    let epsilon = util::mean_obliquity(t) + delta_epsilon;
+
+    let lambda = (theta + delta_psi + ABERRATION / r).normalize_radians();
+
+    let (lambda_sin, lambda_cos) = lambda.sin_cos();
+    let (epsilon_sin, epsilon_cos) = epsilon.sin_cos();
+    let alpha = (lambda_sin * epsilon_cos - beta.tan() * epsilon_sin)
+        .atan2(lambda_cos)
+        .normalize_radians();
+    let delta = (beta.sin() * epsilon_cos + beta.cos() * epsilon_sin * lambda_sin).asin();
+
+    ApparentCoordinates {
+        alpha,
+        delta,
+        r,
+        equation_of_equinoxes: delta_psi * epsilon_cos,
+    }
+}
+
+/// High precision model of the sun, based on the truncated VSOP87 theory
+/// including nutation and aberration
+pub struct Vsop87Sun;
+impl SkyObject for Vsop87Sun {
+    fn period(&self) -> Duration {
+        Duration::try_days(1).expect("constant value")
+    }
+
+    // source: Reda, Andreas: Solar Position Algorithm for Solar Radiation Applications (NREL)
+    fn position(&self, time: &NaiveDateTime, location: &Location) -> SkyPosition {
+        let jde = julian::ephemeris_day_of(time);
+        let ApparentCoordinates {
+            alpha,
+            delta,
+            r,
+            equation_of_equinoxes,
+            ..
+        } = apparent_coordinates(jde);
+
+        // The conversion uses the mean sidereal time, shifting the right ascension
+        // by the equation of the equinoxes yields the apparent hour angle
+        let alpha = alpha - equation_of_equinoxes;
+        let (altitude, azimuth) =
+            util::convert_ecliptic_to_horizontal(time, location, alpha, delta);
+
+        // Correct for parallax
+        let altitude = altitude - SUN_PARALLAX / r * altitude.cos();
+
+        let altitude = util::refraction(altitude, location);
+
+        SkyPosition {
+            altitude: Radians(altitude),
+            azimuth: Radians(azimuth),
+        }
+    }
+
+    fn angular_radius(&self, time: &NaiveDateTime, _location: &Location) -> Radians {
+        let (_, _, r) = earth_heliocentric(julian::ephemeris_day_of(time));
+
+        Radians(SUN_SEMIDIAMETER / r)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::util::assert_degrees_eq;
+
+    // source: NREL solar position algorithm, example in appendix A.5
+    #[test]
+    fn earth_heliocentric_spa() {
+        let jde = 2452930.312847 + 67. / 86400.;
+
+        let (l, b, r) = earth_heliocentric(jde);
+
+        assert_degrees_eq(Radians(l), 24.0182616917, 1e-6);
+        assert_degrees_eq(Radians(b), -0.0001011219, 1e-6);
+        assert!((r - 0.9965422974).abs() < 1e-8);
+    }
+
+    // source: NREL solar position algorithm, example in appendix A.5
+    #[test]
+    fn apparent_coordinates_spa() {
+        let jde = 2452930.312847 + 67. / 86400.;
+
+        let ApparentCoordinates { alpha, delta, .. } = apparent_coordinates(jde);
+
+        assert_degrees_eq(Radians(alpha), 202.22741, 2e-4);
+        assert_degrees_eq(Radians(delta), -9.31434, 2e-4);
+    }
+
+    // source: Meeus, Astronomical Algorithms, example 25.b
+    #[test]
+    fn earth_and_apparent_coordinates_meeus() {
+        let jde = 2448908.5;
+
+        let (l, b, r) = earth_heliocentric(jde);
+
+        assert_degrees_eq(Radians(l), 19.907372, 1e-6);
+        assert_degrees_eq(Radians(b), -0.000179, 1e-6);
+        assert!((r - 0.99760775).abs() < 1e-8);
+
+        let ApparentCoordinates { alpha, delta, .. } = apparent_coordinates(jde);
+
+        assert_degrees_eq(Radians(alpha), 198.378121, 2e-4);
+        assert_degrees_eq(Radians(delta), -7.783817, 2e-4);
+    }
+
+    // source: USNO, Earth's seasons, equinoxes and solstices rounded to the minute (UT)
+    const SEASONS: [(&str, f64); 20] = [
+        ("2000-03-20 07:35", 0.),
+        ("2000-06-21 01:48", 90.),
+        ("2000-09-22 17:28", 180.),
+        ("2000-12-21 13:37", 270.),
+        ("2010-03-20 17:32", 0.),
+        ("2010-06-21 11:28", 90.),
+        ("2010-09-23 03:09", 180.),
+        ("2010-12-21 23:38", 270.),
+        ("2020-03-20 03:50", 0.),
+        ("2020-06-20 21:44", 90.),
+        ("2020-09-22 13:31", 180.),
+        ("2020-12-21 10:02", 270.),
+        ("2023-03-20 21:24", 0.),
+        ("2023-06-21 14:57", 90.),
+        ("2023-09-23 06:50", 180.),
+        ("2023-12-22 03:27", 270.),
+        ("2024-03-20 03:06", 0.),
+        ("2024-06-20 20:51", 90.),
+        ("2024-09-22 12:44", 180.),
+        ("2024-12-21 09:20", 270.),
+    ];
+
+    /// At an equinox or a solstice the apparent longitude of the sun, and with it its right
+    /// ascension, is a multiple of 90°. At the equinoxes the sun is on the celestial equator,
+    /// at the solstices it is as far from it as the ecliptic is tilted.
+    #[test]
+    fn equinoxes_and_solstices() {
+        for (time, right_ascension) in SEASONS {
+            let time = NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M").unwrap();
+            let ApparentCoordinates { alpha, delta, .. } =
+                apparent_coordinates(julian::ephemeris_day_of(&time));
+
+            // The sun moves by less than 0.001° in right ascension in half a minute
+            let alpha = Radians(alpha).to_degrees().0.rem_euclid(360.);
+            let alpha = if alpha > 315. { alpha - 360. } else { alpha };
+            assert!(
+                (alpha - right_ascension).abs() < 1e-3,
+                "{time}: {alpha} (is) differs from {right_ascension} (want)"
+            );
+
+            if right_ascension % 180. == 0. {
+                assert_degrees_eq(Radians(delta), 0., 1e-3);
+            } else {
+                assert_degrees_eq(Radians(delta.abs()), 23.44, 5e-3);
+            }
+        }
+    }
+}
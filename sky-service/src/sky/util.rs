@@ -2,7 +2,12 @@ use std::f64::consts::{FRAC_PI_2, PI, TAU};
 
 use chrono::{NaiveDateTime, Timelike};
 
-use crate::{angle::AngleExtensions, julian, Location};
+use crate::{
+    angle::AngleExtensions,
+    julian,
+    location::{KELVIN, STANDARD_PRESSURE, STANDARD_TEMPERATURE},
+    Location,
+};
 
 const SECONDS_PER_HOUR: f64 = 60. * 60.;
 const NANOSECONDS_PER_HOUR: f64 = SECONDS_PER_HOUR * 1e9;
@@ -16,7 +21,13 @@ const REFRACTION_C1: f64 = 10.3;
 const REFRACTION_C2: f64 = 5.11;
 const REFRACTION_C3: f64 = 60.;
 
-fn sidereal_time(time: &NaiveDateTime, location: &Location, alpha: f64) -> f64 {
+pub const ARCSECONDS: f64 = PI / (180. * 3600.);
+
+const EARTH_RADIUS: f64 = 6378140.; // metres
+const EARTH_AXIS_RATIO: f64 = 0.99664719;
+
+/// Local hour angle of an object with the right ascension `alpha`
+pub fn sidereal_time(time: &NaiveDateTime, location: &Location, alpha: f64) -> f64 {
     let t0 = julian::centuries_of_midnight_since_2000(time);
     let t = time.num_seconds_from_midnight() as f64 / SECONDS_PER_HOUR
         + time.nanosecond() as f64 / NANOSECONDS_PER_HOUR;
@@ -58,12 +69,108 @@ pub fn convert_ecliptic_to_horizontal(
     (altitude, azimuth)
 }
 
-pub fn refraction(altitude: f64) -> f64 {
+/// Apparent altitude of an object at the true `altitude`, for the atmospheric conditions
+/// at the location
+///
+/// source: Meeus, Astronomical Algorithms, chapter 16
+pub fn refraction(altitude: f64, location: &Location) -> f64 {
     let altitude = altitude.to_degrees();
     let r = REFRACTION_C0
         / (altitude + REFRACTION_C1 / (altitude + REFRACTION_C2))
             .to_radians()
             .tan();
+    let r = r * location.pressure() / STANDARD_PRESSURE * (KELVIN + STANDARD_TEMPERATURE)
+        / (KELVIN + location.temperature());
 
     (altitude + r / REFRACTION_C3).to_radians()
 }
+
+/// Nutation in longitude and obliquity (in radians) for `t` julian centuries since J2000
+///
+/// source: Meeus, Astronomical Algorithms, chapter 22 (accurate to 0.5")
+pub fn nutation(t: f64) -> (f64, f64) {
+    let omega = (125.04452 - 1934.136261 * t).to_radians();
+    let l_sun = (280.4665 + 36000.7698 * t).to_radians();
+    let l_moon = (218.3165 + 481267.8813 * t).to_radians();
+
+    let delta_psi = -17.20 * omega.sin() - 1.32 * (2. * l_sun).sin() - 0.23 * (2. * l_moon).sin()
+        + 0.21 * (2. * omega).sin();
+    let delta_epsilon = 9.20 * omega.cos() + 0.57 * (2. * l_sun).cos() + 0.10 * (2. * l_moon).cos()
+        - 0.09 * (2. * omega).cos();
+
+    (delta_psi * ARCSECONDS, delta_epsilon * ARCSECONDS)
+}
+
+/// Mean obliquity of the ecliptic (in radians) for `t` julian centuries since J2000
+///
+/// source: Meeus, Astronomical Algorithms, chapter 22
+pub fn mean_obliquity(t: f64) -> f64 {
+    (84381.448 - 46.8150 * t - 0.00059 * t.powi(2) + 0.001813 * t.powi(3)) * ARCSECONDS
+}
+
+/// The geocentric coordinates `(rho * sin(phi'), rho * cos(phi'))` of an observer
+/// at the latitude `lat` (in radians) and `height` metres above sea level
+///
+/// source: Meeus, Astronomical Algorithms, chapter 11
+pub fn observer_geocentric(lat: f64, height: f64) -> (f64, f64) {
+    let u = (EARTH_AXIS_RATIO * lat.tan()).atan();
+    let height = height / EARTH_RADIUS;
+
+    let rho_sin = EARTH_AXIS_RATIO * u.sin() + height * lat.sin();
+    let rho_cos = u.cos() + height * lat.cos();
+
+    (rho_sin, rho_cos)
+}
+
+/// Topocentric right ascension and declination of an object with the geocentric coordinates
+/// `alpha` and `delta`, the hour angle `hour_angle` and the horizontal parallax `parallax`,
+/// seen by an observer at the latitude `lat` (in radians) and `height` metres above sea level
+///
+/// source: Meeus, Astronomical Algorithms, chapter 40
+pub fn topocentric_equatorial(
+    alpha: f64,
+    delta: f64,
+    hour_angle: f64,
+    parallax: f64,
+    lat: f64,
+    height: f64,
+) -> (f64, f64) {
+    let (rho_sin, rho_cos) = observer_geocentric(lat, height);
+    let parallax_sin = parallax.sin();
+    let (hour_angle_sin, hour_angle_cos) = hour_angle.sin_cos();
+
+    let delta_alpha = (-rho_cos * parallax_sin * hour_angle_sin)
+        .atan2(delta.cos() - rho_cos * parallax_sin * hour_angle_cos);
+    let delta_topo = ((delta.sin() - rho_sin * parallax_sin) * delta_alpha.cos())
+        .atan2(delta.cos() - rho_cos * parallax_sin * hour_angle_cos);
+
+    ((alpha + delta_alpha).normalize_radians(), delta_topo)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // source: Meeus, Astronomical Algorithms, example 40.a
+    #[test]
+    fn topocentric_equatorial_meeus() {
+        let lat = (33f64 + 21. / 60. + 22. / 3600.).to_radians();
+        let height = 1706.;
+
+        let (rho_sin, rho_cos) = observer_geocentric(lat, height);
+        assert!((rho_sin - 0.546861).abs() < 1e-6);
+        assert!((rho_cos - 0.836339).abs() < 1e-6);
+
+        let (alpha, delta) = topocentric_equatorial(
+            339.530208f64.to_radians(),
+            (-15.771083f64).to_radians(),
+            288.7958f64.to_radians(),
+            23.592 * ARCSECONDS,
+            lat,
+            height,
+        );
+
+        assert!((alpha.to_degrees() - 339.535583).abs() < 1e-4);
+        assert!((delta.to_degrees() - -15.775).abs() < 1e-4);
+    }
+}
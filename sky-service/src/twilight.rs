@@ -0,0 +1,156 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    angle::{Degrees, Radians},
+    calculate_rise_and_set_with_offset, Horizon, HorizonEventsResult, HorizonEventsState,
+    HorizonWindow, Location, SkyObject,
+};
+
+const CIVIL_OFFSET: Degrees = Degrees(-6.);
+const NAUTICAL_OFFSET: Degrees = Degrees(-12.);
+const ASTRONOMICAL_OFFSET: Degrees = Degrees(-18.);
+
+const GOLDEN_HOUR_UPPER: Degrees = Degrees(6.);
+const GOLDEN_HOUR_LOWER: Degrees = Degrees(0.);
+const BLUE_HOUR_UPPER: Degrees = Degrees(-4.);
+const BLUE_HOUR_LOWER: Degrees = CIVIL_OFFSET;
+
+/// Twilight phases, defined by how far the sun is below the horizon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Twilight {
+    Civil,
+    Nautical,
+    Astronomical,
+}
+
+impl Twilight {
+    /// Altitude relative to the horizon at which the twilight phase ends in the morning
+    /// and begins in the evening
+    pub fn offset(&self) -> Radians {
+        let offset = match self {
+            Twilight::Civil => CIVIL_OFFSET,
+            Twilight::Nautical => NAUTICAL_OFFSET,
+            Twilight::Astronomical => ASTRONOMICAL_OFFSET,
+        };
+
+        offset.to_radians()
+    }
+}
+
+/// Twilight boundaries relative to the horizon.
+///
+/// The `rise` of each entry is the morning event (dawn), the `set` is the evening event (dusk).
+#[derive(Serialize, Deserialize)]
+pub struct TwilightEvents {
+    pub civil: HorizonEventsResult,
+    pub nautical: HorizonEventsResult,
+    pub astronomical: HorizonEventsResult,
+}
+
+pub fn calculate_twilight_events<O>(
+    object: &O,
+    time: &NaiveDateTime,
+    location: &Location,
+    horizon: &Horizon,
+) -> TwilightEvents
+where
+    O: SkyObject,
+{
+    let events = |twilight: Twilight| {
+        calculate_rise_and_set_with_offset(object, time, location, horizon, twilight.offset())
+            .into()
+    };
+
+    TwilightEvents {
+        civil: events(Twilight::Civil),
+        nautical: events(Twilight::Nautical),
+        astronomical: events(Twilight::Astronomical),
+    }
+}
+
+/// Windows in the morning and the evening during which the object is between two offsets
+/// relative to the horizon
+#[derive(Serialize, Deserialize)]
+pub struct HorizonWindows {
+    /// `AlwaysUp` if the object does not go below the lower offset, `AlwaysDown` if it does
+    /// not rise above the upper one. The windows are only found in the state `Events`.
+    pub state: HorizonEventsState,
+    pub morning: Option<HorizonWindow>,
+    pub evening: Option<HorizonWindow>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct LightWindows {
+    pub golden_hour: HorizonWindows,
+    pub blue_hour: HorizonWindows,
+}
+
+pub fn calculate_light_windows<O>(
+    object: &O,
+    time: &NaiveDateTime,
+    location: &Location,
+    horizon: &Horizon,
+) -> LightWindows
+where
+    O: SkyObject,
+{
+    let windows = |lower: Degrees, upper: Degrees| {
+        calculate_horizon_windows(
+            object,
+            time,
+            location,
+            horizon,
+            lower.to_radians(),
+            upper.to_radians(),
+        )
+    };
+
+    LightWindows {
+        golden_hour: windows(GOLDEN_HOUR_LOWER, GOLDEN_HOUR_UPPER),
+        blue_hour: windows(BLUE_HOUR_LOWER, BLUE_HOUR_UPPER),
+    }
+}
+
+/// Calculate when the object passes between the `lower` and `upper` offsets relative to
+/// the horizon.
+pub fn calculate_horizon_windows<O>(
+    object: &O,
+    time: &NaiveDateTime,
+    location: &Location,
+    horizon: &Horizon,
+    lower: Radians,
+    upper: Radians,
+) -> HorizonWindows
+where
+    O: SkyObject,
+{
+    let lower: HorizonEventsResult =
+        calculate_rise_and_set_with_offset(object, time, location, horizon, lower).into();
+    let upper: HorizonEventsResult =
+        calculate_rise_and_set_with_offset(object, time, location, horizon, upper).into();
+
+    let state = match (lower.state(), upper.state()) {
+        (HorizonEventsState::Events, HorizonEventsState::Events) => HorizonEventsState::Events,
+        (HorizonEventsState::SolverFailed, _) | (_, HorizonEventsState::SolverFailed) => {
+            HorizonEventsState::SolverFailed
+        }
+        (HorizonEventsState::AlwaysUp, _) => HorizonEventsState::AlwaysUp,
+        (_, HorizonEventsState::AlwaysDown) => HorizonEventsState::AlwaysDown,
+        // The upper offset can not be crossed without crossing the lower one
+        _ => HorizonEventsState::SolverFailed,
+    };
+
+    match (lower.events(), upper.events()) {
+        (Some(lower), Some(upper)) => HorizonWindows {
+            state,
+            morning: HorizonWindow::between(&lower.rise, &upper.rise),
+            evening: HorizonWindow::between(&upper.set, &lower.set),
+        },
+        _ => HorizonWindows {
+            state,
+            morning: None,
+            evening: None,
+        },
+    }
+}
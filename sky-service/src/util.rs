@@ -1,3 +1,5 @@
+use crate::angle::Radians;
+
 fn assert_epsilon_eq(is: f64, want: f64, eps: f64) {
     let diff = (is - want).abs();
     assert!(
@@ -13,3 +15,13 @@ pub fn assert_approx_eq(is: f64, want: f64) {
 pub fn assert_precisely_eq(is: f64, want: f64) {
     assert_epsilon_eq(is, want, 1e-12)
 }
+
+/// Compare an angle with a reference value, both `want` and `eps` are given in degrees
+pub fn assert_degrees_eq(is: Radians, want: f64, eps: f64) {
+    let is = is.to_degrees().0;
+    let diff = (is - want).abs();
+    assert!(
+        diff < eps,
+        "{is}° (is) differs from {want}° (want) by {diff}°, which is larger than {eps}° (epsilon)"
+    );
+}
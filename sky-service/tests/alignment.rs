@@ -0,0 +1,128 @@
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime};
+
+use sky_service::alignment::{
+    angular_distance, find_events_in_azimuth_range, find_landmark_alignments, AzimuthRange,
+    Landmark,
+};
+use sky_service::angle::{Degrees, Radians};
+use sky_service::crossing::EventKind;
+use sky_service::horizon::HORIZON_ANGLE;
+use sky_service::sky::sun::Sun;
+use sky_service::Horizon;
+use sky_service::Location;
+use sky_service::SkyObject;
+use sky_service::HORIZON_SAMPLES;
+
+#[test]
+fn sunsets_in_the_north_west() {
+    let horizon = Horizon::new([0.; HORIZON_SAMPLES]);
+
+    let time = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+
+    let location = Location {
+        lat: 48.1,
+        lon: 11.6,
+        ..Default::default()
+    };
+
+    // The sun only sets this far north around the summer solstice
+    let range = AzimuthRange {
+        start: Degrees(300.).to_radians(),
+        end: Degrees(320.).to_radians(),
+    };
+
+    let matches = find_events_in_azimuth_range(&Sun, &time, 366, &location, &horizon, &range);
+
+    assert!(!matches.is_empty());
+    for azimuth_match in &matches {
+        assert_eq!(azimuth_match.kind, EventKind::Set);
+        assert!((5..=7).contains(&azimuth_match.event.time.month()));
+        assert!(range.contains(azimuth_match.event.azimuth));
+    }
+    assert!(matches
+        .windows(2)
+        .all(|pair| pair[0].event.time < pair[1].event.time));
+}
+
+#[test]
+fn sunset_in_a_notch() {
+    // A ridge of 10° in the west with a notch down to the sea-level horizon
+    let altitudes: Vec<f64> = (0..HORIZON_SAMPLES)
+        .map(|i| {
+            let azimuth = Radians(i as f64 * HORIZON_ANGLE).to_degrees();
+            let ridge = (Degrees(230.)..=Degrees(310.)).contains(&azimuth);
+            let notch = (Degrees(262.)..=Degrees(278.)).contains(&azimuth);
+            if ridge && !notch {
+                Degrees(10.).to_radians().0
+            } else {
+                0.
+            }
+        })
+        .collect();
+    let horizon = Horizon::new(altitudes);
+
+    let time = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2024, 3, 20).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+
+    let location = Location {
+        lat: 48.1,
+        lon: 11.6,
+        ..Default::default()
+    };
+
+    let range = AzimuthRange {
+        start: Degrees(266.).to_radians(),
+        end: Degrees(276.).to_radians(),
+    };
+
+    // The sun first sets behind the ridge, reappears in the notch and sets again in it
+    let matches = find_events_in_azimuth_range(&Sun, &time, 1, &location, &horizon, &range);
+
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].kind, EventKind::Set);
+    assert!(range.contains(matches[0].event.azimuth));
+    assert!(matches[0].event.altitude < Degrees(1.).to_radians());
+}
+
+#[test]
+fn sun_behind_a_tower_in_the_west() {
+    let time = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+
+    let location = Location {
+        lat: 48.1,
+        lon: 11.6,
+        ..Default::default()
+    };
+
+    // About 2 km due west, its top 2° above the horizon
+    let tower = Landmark {
+        lat: 48.1,
+        lon: 11.573,
+        elevation: 0.,
+        height: 70.,
+    };
+    let target = tower.target(&location);
+    let max_distance = Degrees(0.5).to_radians();
+
+    let alignments = find_landmark_alignments(&Sun, &time, 366, &location, &tower, max_distance);
+
+    // The sun sets due west around the equinoxes
+    assert!(!alignments.is_empty());
+    for alignment in &alignments {
+        assert!([3, 9].contains(&alignment.time.month()));
+
+        let position = Sun.position(&alignment.time.naive_utc(), &location);
+        assert!(angular_distance(&position, &target) <= max_distance);
+    }
+    assert!(alignments
+        .windows(2)
+        .all(|pair| pair[0].time < pair[1].time));
+}
@@ -0,0 +1,244 @@
+use std::f64::consts::{PI, TAU};
+use std::fs;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use proptest::prelude::*;
+
+use sky_service::angle::{Degrees, Radians};
+use sky_service::crossing::{find_crossings, EventKind};
+use sky_service::horizon::HORIZON_ANGLE;
+use sky_service::sky::{moon::Moon, sun::Sun};
+use sky_service::{
+    calculate_rise_and_set, Horizon, HorizonEvent, HorizonEvents, Location, SkyObject, SkyPosition,
+    HORIZON_SAMPLES,
+};
+
+const SIDEREAL_DAY: f64 = 86164.1;
+
+/// A star at a fixed declination, turning with the sky
+struct Star {
+    declination: f64,
+}
+
+impl SkyObject for Star {
+    fn period(&self) -> Duration {
+        Duration::try_seconds(SIDEREAL_DAY as i64).expect("constant value")
+    }
+
+    fn position(&self, time: &NaiveDateTime, location: &Location) -> SkyPosition {
+        let hour_angle = TAU * time.and_utc().timestamp() as f64 / SIDEREAL_DAY;
+        let lat = location.lat.to_radians();
+        let delta = self.declination;
+
+        let altitude =
+            (lat.sin() * delta.sin() + lat.cos() * delta.cos() * hour_angle.cos()).asin();
+        let azimuth = hour_angle
+            .sin()
+            .atan2(hour_angle.cos() * lat.sin() - delta.tan() * lat.cos())
+            + PI;
+
+        SkyPosition {
+            altitude: Radians(altitude),
+            azimuth: Radians(azimuth),
+        }
+    }
+}
+
+/// A horizon through randomly placed peaks, linearly interpolated
+fn horizon(peaks: &[f64]) -> Horizon {
+    let mut altitudes = [0.; HORIZON_SAMPLES];
+    for (i, altitude) in altitudes.iter_mut().enumerate() {
+        let position = (i * peaks.len()) as f64 / HORIZON_SAMPLES as f64;
+        let left = position.floor() as usize;
+        let right = (left + 1) % peaks.len();
+        let share = position - left as f64;
+
+        *altitude = peaks[left] * (1. - share) + peaks[right] * share;
+    }
+
+    Horizon::new(altitudes)
+}
+
+/// Changes between up and down found by checking every second from `start` to `end`
+fn brute_force<O>(
+    object: &O,
+    start: &NaiveDateTime,
+    end: &NaiveDateTime,
+    location: &Location,
+    horizon: &Horizon,
+) -> Vec<(EventKind, NaiveDateTime)>
+where
+    O: SkyObject,
+{
+    let is_up = |time: &NaiveDateTime| {
+        let SkyPosition { altitude, azimuth } = object.position(time, location);
+        altitude > horizon.altitude_at(azimuth).max(-location.dip())
+    };
+
+    let mut changes = Vec::new();
+    let mut up = is_up(start);
+    let mut time = *start;
+    while time < *end {
+        time += Duration::try_seconds(1).unwrap();
+        if is_up(&time) != up {
+            up = !up;
+            let kind = if up { EventKind::Rise } else { EventKind::Set };
+            changes.push((kind, time));
+        }
+    }
+
+    changes
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn crossings_match_brute_force(
+        lat in -70f64..70.,
+        declination in -25f64..25.,
+        peaks in prop::collection::vec(-2f64..25., 3..60),
+        min_seconds in 10i64..600,
+    ) {
+        let star = Star { declination: declination.to_radians() };
+        let peaks: Vec<f64> = peaks.iter().map(|peak| peak.to_radians()).collect();
+        let horizon = horizon(&peaks);
+        let location = Location { lat, lon: 0., ..Default::default() };
+
+        let start = NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+        );
+        let end = start + star.period();
+        let min_duration = Duration::try_seconds(min_seconds).unwrap();
+
+        let found: Vec<(EventKind, NaiveDateTime)> =
+            find_crossings(&star, &start, &end, &location, &horizon, Radians(0.), min_duration)
+                .into_iter()
+                .map(|crossing| (crossing.kind, crossing.event.time.naive_utc()))
+                .collect();
+        let expected = brute_force(&star, &start, &end, &location, &horizon);
+
+        let is_near = |(kind, time): &(EventKind, NaiveDateTime),
+                       candidates: &[(EventKind, NaiveDateTime)]| {
+            candidates.iter().any(|(other_kind, other_time)| {
+                kind == other_kind && (*time - *other_time).num_seconds().abs() <= 2
+            })
+        };
+
+        // Every crossing found is a real one
+        for crossing in &found {
+            prop_assert!(is_near(crossing, &expected), "{crossing:?} in {expected:?}");
+        }
+
+        // Every change between periods lasting at least the minimum duration is found
+        for (i, change) in expected.iter().enumerate() {
+            let before = if i == 0 { start } else { expected[i - 1].1 };
+            let after = expected.get(i + 1).map_or(end, |next| next.1);
+            if change.1 - before >= min_duration && after - change.1 >= min_duration {
+                prop_assert!(is_near(change, &found), "{change:?} in {found:?}");
+            }
+        }
+    }
+}
+
+fn read_horizon(path: &str) -> Horizon {
+    Horizon::from_dat(&fs::read_to_string(path).unwrap()).unwrap()
+}
+
+fn assert_matches_brute_force<O>(
+    object: &O,
+    time: NaiveDateTime,
+    location: &Location,
+    horizon: &Horizon,
+) where
+    O: SkyObject,
+{
+    let HorizonEvents { rise, set, .. } =
+        calculate_rise_and_set(object, &time, location, horizon).unwrap();
+    let changes = brute_force(object, &time, &(time + object.period()), location, horizon);
+    // The first rise and the first set within one period
+    let first = |kind: EventKind| changes.iter().find(|change| change.0 == kind).unwrap().1;
+    let (want_rise, want_set) = (first(EventKind::Rise), first(EventKind::Set));
+
+    for (event, want) in [(&rise, want_rise), (&set, want_set)] {
+        let is = event.time.naive_utc();
+        assert!((is - want).num_seconds().abs() <= 1, "{is} != {want}");
+
+        // The event lies on the horizon at its own azimuth
+        let HorizonEvent {
+            altitude, azimuth, ..
+        } = event;
+        let horizon_altitude = horizon.altitude_at(*azimuth).max(-location.dip());
+        assert!((*altitude - horizon_altitude).abs() < Degrees(0.01).to_radians());
+    }
+}
+
+#[test]
+fn sun_and_moon_behind_recorded_horizons() {
+    let cases = [
+        ("tests/Data/aussicht_horizon.dat", 48.818, 9.587),
+        (
+            "tests/Data/horizon-v1.0.0-dd8a326c-5065-5fdb-80ef-d033e6e34270.dat",
+            48.8300769,
+            9.5739522,
+        ),
+    ];
+
+    for (path, lat, lon) in cases {
+        let horizon = read_horizon(path);
+        let location = Location {
+            lat,
+            lon,
+            ..Default::default()
+        };
+
+        for (month, day) in [(3, 20), (6, 21), (10, 14), (12, 21)] {
+            let time = NaiveDateTime::new(
+                NaiveDate::from_ymd_opt(2023, month, day).unwrap(),
+                NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+            );
+
+            assert_matches_brute_force(&Sun, time, &location, &horizon);
+            assert_matches_brute_force(&Moon, time, &location, &horizon);
+        }
+    }
+}
+
+// The horizon is looked up at the azimuth of the sun in radians, a ridge in the west brings
+// the sunset forward but leaves the sunrise unchanged
+#[test]
+fn ridge_in_the_west() {
+    let location = Location {
+        lat: 47.,
+        lon: 11.,
+        ..Default::default()
+    };
+    let time = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2024, 3, 20).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+
+    let flat = Horizon::new([0.; HORIZON_SAMPLES]);
+    let ridge: Vec<f64> = (0..HORIZON_SAMPLES)
+        .map(|i| {
+            let azimuth = Radians(i as f64 * HORIZON_ANGLE).to_degrees();
+            if (Degrees(240.)..=Degrees(300.)).contains(&azimuth) {
+                Degrees(5.).to_radians().0
+            } else {
+                0.
+            }
+        })
+        .collect();
+    let ridge = Horizon::new(ridge);
+
+    let flat_events = calculate_rise_and_set(&Sun, &time, &location, &flat).unwrap();
+    let ridge_events = calculate_rise_and_set(&Sun, &time, &location, &ridge).unwrap();
+
+    assert_eq!(flat_events.rise.time, ridge_events.rise.time);
+    let earlier = flat_events.set.time - ridge_events.set.time;
+    assert!((25..=40).contains(&earlier.num_minutes()), "{earlier}");
+    assert!((ridge_events.set.altitude.to_degrees().0 - 5.).abs() < 0.01);
+
+    assert_matches_brute_force(&Sun, time, &location, &ridge);
+}
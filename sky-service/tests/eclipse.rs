@@ -0,0 +1,136 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+use sky_service::eclipse::{find_eclipses, Eclipse, EclipseKind, PhaseKind};
+use sky_service::Horizon;
+use sky_service::Location;
+use sky_service::HORIZON_SAMPLES;
+
+fn assert_time_eq(is: DateTime<Utc>, want: &str, seconds: i64) {
+    let want = DateTime::parse_from_rfc3339(want).unwrap().to_utc();
+    let diff = (is - want).num_seconds().abs();
+    assert!(
+        diff <= seconds,
+        "{is} (is) differs from {want} (want) by {diff}s"
+    );
+}
+
+fn assert_phase(eclipse: &Eclipse, index: usize, kind: PhaseKind, (start, end): (&str, &str)) {
+    let phase = &eclipse.phases[index];
+    assert_eq!(phase.kind, kind);
+
+    let seconds = if kind == PhaseKind::Penumbral {
+        120
+    } else {
+        60
+    };
+    assert_time_eq(phase.start, start, seconds);
+    assert_time_eq(phase.end, end, seconds);
+}
+
+// source: NASA eclipse website, local circumstances for Dallas
+#[test]
+fn total_solar_eclipse_2024() {
+    let horizon = Horizon::new([0.; HORIZON_SAMPLES]);
+
+    let time = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2024, 4, 7).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+
+    let dallas = Location {
+        lat: 32.7767,
+        lon: -96.797,
+        ..Default::default()
+    };
+
+    let eclipses = find_eclipses(&time, 3, &dallas, &horizon);
+
+    assert_eq!(eclipses.len(), 1);
+    let eclipse = &eclipses[0];
+    assert_eq!(eclipse.kind, EclipseKind::Solar);
+    assert_eq!(eclipse.phase(), Some(PhaseKind::Total));
+    assert_time_eq(eclipse.maximum, "2024-04-08T18:42:39Z", 60);
+    assert_phase(
+        eclipse,
+        0,
+        PhaseKind::Partial,
+        ("2024-04-08T17:23:10Z", "2024-04-08T20:02:37Z"),
+    );
+    assert_phase(
+        eclipse,
+        1,
+        PhaseKind::Total,
+        ("2024-04-08T18:40:44Z", "2024-04-08T18:44:35Z"),
+    );
+
+    // The sun is high in the sky during the whole eclipse
+    for phase in &eclipse.phases {
+        assert_eq!(phase.visible.len(), 1);
+        assert_eq!(phase.visible[0].start, phase.start);
+        assert_eq!(phase.visible[0].end, phase.end);
+    }
+}
+
+// source: NASA eclipse website, total lunar eclipse of 2022 November 8
+#[test]
+fn total_lunar_eclipse_2022() {
+    let horizon = Horizon::new([0.; HORIZON_SAMPLES]);
+
+    let time = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2022, 11, 7).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+
+    let denver = Location {
+        lat: 39.74,
+        lon: -104.99,
+        ..Default::default()
+    };
+
+    let eclipses = find_eclipses(&time, 3, &denver, &horizon);
+
+    assert_eq!(eclipses.len(), 1);
+    let eclipse = &eclipses[0];
+    assert_eq!(eclipse.kind, EclipseKind::Lunar);
+    assert_eq!(eclipse.phase(), Some(PhaseKind::Total));
+    assert_time_eq(eclipse.maximum, "2022-11-08T10:59:11Z", 60);
+    assert_phase(
+        eclipse,
+        0,
+        PhaseKind::Penumbral,
+        ("2022-11-08T08:02:15Z", "2022-11-08T13:56:09Z"),
+    );
+    assert_phase(
+        eclipse,
+        1,
+        PhaseKind::Partial,
+        ("2022-11-08T09:09:12Z", "2022-11-08T12:49:03Z"),
+    );
+    assert_phase(
+        eclipse,
+        2,
+        PhaseKind::Total,
+        ("2022-11-08T10:16:39Z", "2022-11-08T11:41:36Z"),
+    );
+
+    // The moon sets shortly before the end of the penumbral phase
+    let penumbral = &eclipse.phases[0];
+    assert_eq!(penumbral.visible.len(), 1);
+    assert!(penumbral.visible[0].set.is_some());
+    assert!(penumbral.visible[0].end < penumbral.end);
+    let total = &eclipse.phases[2];
+    assert_eq!(total.visible.len(), 1);
+    assert_eq!(total.visible[0].start, total.start);
+    assert_eq!(total.visible[0].end, total.end);
+
+    // In Europe the moon has already set
+    let berlin = Location {
+        lat: 52.52,
+        lon: 13.4,
+        ..Default::default()
+    };
+    let eclipses = find_eclipses(&time, 3, &berlin, &horizon);
+
+    assert_eq!(eclipses.len(), 1);
+    assert!(!eclipses[0].is_visible());
+}
@@ -0,0 +1,46 @@
+use std::fs;
+
+use sky_service::angle::Radians;
+use sky_service::horizon::{formats::StellariumMode, HORIZON_ANGLE};
+use sky_service::{Horizon, HORIZON_SAMPLES};
+
+const DATA_FILES: [&str; 3] = [
+    "tests/Data/aussicht_horizon.dat",
+    "tests/Data/horizon-v1.0.0-30752d0b-cfe7-5d6b-9bd0-61cea706c6ea.dat",
+    "tests/Data/horizon-v1.0.0-dd8a326c-5065-5fdb-80ef-d033e6e34270.dat",
+];
+
+#[test]
+fn dat_files_survive_a_round_trip() {
+    for path in DATA_FILES {
+        let horizon = Horizon::from_dat(&fs::read_to_string(path).unwrap()).unwrap();
+
+        assert_eq!(horizon.samples(), HORIZON_SAMPLES);
+        assert_eq!(
+            Horizon::from_dat(&horizon.to_dat()).unwrap(),
+            horizon,
+            "{path}"
+        );
+    }
+}
+
+#[test]
+fn recorded_horizons_survive_other_formats() {
+    for path in DATA_FILES {
+        let horizon = Horizon::from_dat(&fs::read_to_string(path).unwrap()).unwrap();
+
+        let from_csv = Horizon::from_csv(&horizon.to_csv()).unwrap();
+        let from_stellarium =
+            Horizon::from_stellarium(&horizon.to_stellarium(), StellariumMode::AzDegAltDeg)
+                .unwrap();
+
+        // Both formats are written in degrees, rounded to six decimals
+        for other in [from_csv, from_stellarium] {
+            for i in 0..HORIZON_SAMPLES {
+                let azimuth = Radians(i as f64 * HORIZON_ANGLE);
+                let diff = (other.altitude_at(azimuth) - horizon.altitude_at(azimuth)).abs();
+                assert!(diff < Radians(1e-7), "{path}: {diff:?} at sample {i}");
+            }
+        }
+    }
+}
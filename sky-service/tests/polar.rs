@@ -0,0 +1,98 @@
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+
+use sky_service::sky::sun::Sun;
+use sky_service::twilight::{
+    calculate_light_windows, calculate_twilight_events, LightWindows, TwilightEvents,
+};
+use sky_service::Horizon;
+use sky_service::HorizonEventsResult;
+use sky_service::HorizonEventsState;
+use sky_service::Location;
+use sky_service::HORIZON_SAMPLES;
+
+fn sun_events(date: NaiveDate, lat: f64, horizon_altitude: f64) -> HorizonEventsResult {
+    let horizon = Horizon::new([horizon_altitude.to_radians(); HORIZON_SAMPLES]);
+
+    let time = NaiveDateTime::new(date, NaiveTime::from_hms_opt(0, 0, 0).unwrap());
+
+    let location = Location {
+        lat,
+        lon: 15.6,
+        ..Default::default()
+    };
+
+    sky_service::calculate_rise_and_set(&Sun, &time, &location, &horizon).into()
+}
+
+#[test]
+fn midnight_sun() {
+    let date = NaiveDate::from_ymd_opt(2024, 6, 21).unwrap();
+
+    assert!(matches!(
+        sun_events(date, 78.2, 0.),
+        HorizonEventsResult::AlwaysUp
+    ));
+}
+
+#[test]
+fn polar_night() {
+    let date = NaiveDate::from_ymd_opt(2024, 12, 21).unwrap();
+
+    assert!(matches!(
+        sun_events(date, 78.2, 0.),
+        HorizonEventsResult::AlwaysDown
+    ));
+}
+
+#[test]
+fn deep_valley_in_winter() {
+    let date = NaiveDate::from_ymd_opt(2024, 12, 21).unwrap();
+
+    // The sun culminates at about 18° at this latitude
+    assert!(matches!(
+        sun_events(date, 48.1, 25.),
+        HorizonEventsResult::AlwaysDown
+    ));
+    assert!(matches!(
+        sun_events(date, 48.1, 10.),
+        HorizonEventsResult::Events(_)
+    ));
+}
+
+#[test]
+fn polar_twilight() {
+    let horizon = Horizon::new([0.; HORIZON_SAMPLES]);
+    let location = Location {
+        lat: 78.2,
+        lon: 15.6,
+        ..Default::default()
+    };
+
+    // In the polar night the sun culminates at about -11.6°
+    let winter = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2024, 12, 21).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+    let TwilightEvents {
+        civil, nautical, ..
+    } = calculate_twilight_events(&Sun, &winter, &location, &horizon);
+    assert!(matches!(civil, HorizonEventsResult::AlwaysDown));
+    assert!(matches!(nautical, HorizonEventsResult::Events(_)));
+
+    // Under the midnight sun it stays above 11° and never reaches the golden hour
+    let summer = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2024, 6, 21).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+    let TwilightEvents { civil, .. } =
+        calculate_twilight_events(&Sun, &summer, &location, &horizon);
+    assert!(matches!(civil, HorizonEventsResult::AlwaysUp));
+
+    let LightWindows {
+        golden_hour,
+        blue_hour,
+    } = calculate_light_windows(&Sun, &summer, &location, &horizon);
+    assert_eq!(golden_hour.state, HorizonEventsState::AlwaysUp);
+    assert!(golden_hour.morning.is_none() && golden_hour.evening.is_none());
+    assert_eq!(blue_hour.state, HorizonEventsState::AlwaysUp);
+}
@@ -1,22 +1,16 @@
 use std::fs;
 
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
-use sky_service::{sky::moon::Moon, Horizon, HorizonEvents, Location, HORIZON_SAMPLES};
+use sky_service::{sky::moon::Moon, Horizon, HorizonEvents, HorizonEventsResult, Location};
 
 fn find_range(
     uuid: &str,
     time: NaiveDateTime,
     location: Location,
 ) -> Result<HorizonEvents, anyhow::Error> {
-    let mut horizon = Vec::new();
     let altitudes_data =
         fs::read_to_string(format!("tests/Data/horizon-v1.0.0-{}.dat", uuid)).unwrap();
-    for line in altitudes_data.split('\n').filter(|l| !l.is_empty()) {
-        let val = line.parse::<f64>().unwrap();
-        horizon.push(val);
-    }
-    let altitudes: [f64; HORIZON_SAMPLES] = horizon.try_into().unwrap();
-    let horizon = Horizon::new(altitudes);
+    let horizon = Horizon::from_dat(&altitudes_data).unwrap();
 
     sky_service::calculate_rise_and_set(&Moon, &time, &location, &horizon)
 }
@@ -27,6 +21,7 @@ fn test_no_range_found1() {
     let location = Location {
         lat: 48.8300769,
         lon: 9.5739522,
+        ..Default::default()
     };
     let time = NaiveDateTime::new(
         NaiveDate::from_ymd_opt(2023, 10, 16).unwrap(),
@@ -35,20 +30,31 @@ fn test_no_range_found1() {
     find_range(horizon_uuid, time, location).unwrap();
 }
 
-// This one should panic
+// The moon is up at the start, sets behind the terrain in the evening and rises the next
+// afternoon
 #[test]
 fn test_no_range_found2() {
     let horizon_uuid = "dd8a326c-5065-5fdb-80ef-d033e6e34270";
     let location = Location {
         lat: 48.81855,
         lon: 9.5868,
+        ..Default::default()
     };
     let time = NaiveDateTime::new(
         NaiveDate::from_ymd_opt(2023, 10, 19).unwrap(),
         NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
     );
     let res = find_range(horizon_uuid, time, location);
-    assert!(res.is_err_and(|err| {
-        err.to_string() == "could not determine rise and set candidate ranges"
-    }))
+    let HorizonEventsResult::Events(HorizonEvents { rise, set, .. }) = res.into() else {
+        panic!("the moon crosses the horizon");
+    };
+
+    let expected_set =
+        NaiveDateTime::new(time.date(), NaiveTime::from_hms_opt(18, 26, 44).unwrap());
+    let expected_rise = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2023, 10, 20).unwrap(),
+        NaiveTime::from_hms_opt(13, 2, 32).unwrap(),
+    );
+    assert!((set.time.naive_utc() - expected_set).num_seconds().abs() <= 1);
+    assert!((rise.time.naive_utc() - expected_rise).num_seconds().abs() <= 1);
 }
@@ -5,14 +5,21 @@ use std::f64::consts::PI;
 
 use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
 
-use sky_service::sky::sun::Sun;
+use sky_service::angle::{Degrees, Radians};
+use sky_service::sky::{sun::Sun, Contact, Limb};
+use sky_service::twilight::{
+    calculate_light_windows, calculate_twilight_events, HorizonWindows, LightWindows,
+    TwilightEvents,
+};
 use sky_service::Horizon;
 use sky_service::HorizonEvent;
 use sky_service::HorizonEvents;
+use sky_service::HorizonEventsResult;
 use sky_service::Location;
 use sky_service::SkyObject;
 use sky_service::SkyPosition;
 use sky_service::HORIZON_SAMPLES;
+use sky_service::{calculate_sunlight, calculate_track, calculate_visibility_intervals};
 
 const SECONDS_IN_DAY: u32 = 24 * 60 * 60;
 
@@ -20,7 +27,7 @@ struct TestSkyObject;
 
 impl SkyObject for TestSkyObject {
     fn period(&self) -> Duration {
-        Duration::days(1)
+        Duration::try_days(1).expect("constant value")
     }
 
     fn position(&self, time: &NaiveDateTime, _location: &Location) -> SkyPosition {
@@ -29,7 +36,18 @@ impl SkyObject for TestSkyObject {
         let azimuth = 2. * PI * (seconds / SECONDS_IN_DAY as f64);
         let altitude = -(PI / 2.) * azimuth.cos();
 
-        SkyPosition { altitude, azimuth }
+        SkyPosition {
+            altitude: Radians(altitude),
+            azimuth: Radians(azimuth),
+        }
+    }
+
+    fn angular_radius(&self, _time: &NaiveDateTime, _location: &Location) -> Radians {
+        Degrees(1.).to_radians()
+    }
+
+    fn max_angular_speed(&self) -> f64 {
+        PI * PI / SECONDS_IN_DAY as f64
     }
 }
 
@@ -46,11 +64,13 @@ fn set_flat() {
     let location = Location {
         lat: 48.1,
         lon: 11.6,
+        ..Default::default()
     };
 
     let HorizonEvents {
         rise: HorizonEvent { time: rise, .. },
         set: HorizonEvent { time: set, .. },
+        ..
     } = sky_service::calculate_rise_and_set(&TestSkyObject, &time, &location, &horizon).unwrap();
 
     assert_eq!(rise.hour(), 6);
@@ -75,11 +95,13 @@ fn sanity_test_sun() {
     let location = Location {
         lat: 48.1,
         lon: 11.6,
+        ..Default::default()
     };
 
     let HorizonEvents {
         rise: HorizonEvent { time: rise, .. },
         set: HorizonEvent { time: set, .. },
+        ..
     } = sky_service::calculate_rise_and_set(&Sun, &time, &location, &horizon).unwrap();
 
     assert_eq!(rise.hour(), 3);
@@ -88,3 +110,386 @@ fn sanity_test_sun() {
     assert_eq!(set.hour(), 18);
     assert_eq!(set.minute(), 40);
 }
+
+#[test]
+fn transit_and_duration_flat() {
+    let altitudes = [0.; HORIZON_SAMPLES];
+    let horizon = Horizon::new(altitudes);
+
+    let location = Location {
+        lat: 48.1,
+        lon: 11.6,
+        ..Default::default()
+    };
+
+    let midnight = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2006, 8, 6).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+
+    let HorizonEvents {
+        transit, duration, ..
+    } = sky_service::calculate_rise_and_set(&TestSkyObject, &midnight, &location, &horizon)
+        .unwrap();
+
+    let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+    assert!((transit.time.time() - noon).num_seconds().abs() <= 1);
+    assert!((transit.altitude.0 - PI / 2.).abs() < 1e-4);
+    assert_eq!(duration, 12 * 60 * 60);
+
+    // Starting at noon, the object sets first and is up again at the end of the period
+    let HorizonEvents {
+        transit, duration, ..
+    } = sky_service::calculate_rise_and_set(
+        &TestSkyObject,
+        &midnight.with_hour(12).unwrap(),
+        &location,
+        &horizon,
+    )
+    .unwrap();
+
+    assert!((transit.time.time() - noon).num_seconds().abs() <= 1);
+    assert_eq!(duration, 12 * 60 * 60);
+
+    // The solar noon in Munich is around 13:19 CEST
+    let HorizonEvents {
+        transit, duration, ..
+    } = sky_service::calculate_rise_and_set(&Sun, &midnight, &location, &horizon).unwrap();
+
+    assert_eq!(transit.time.hour(), 11);
+    assert_eq!(transit.time.minute(), 19);
+    assert!((transit.azimuth.to_degrees().0 - 180.).abs() < 0.5);
+    assert!((duration - (14 * 60 + 43) * 60).abs() < 120);
+}
+
+#[test]
+fn twilight_flat() {
+    let altitudes = [0.; HORIZON_SAMPLES];
+    let horizon = Horizon::new(altitudes);
+
+    let time = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2006, 8, 6).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+
+    let location = Location {
+        lat: 48.1,
+        lon: 11.6,
+        ..Default::default()
+    };
+
+    let TwilightEvents {
+        civil:
+            HorizonEventsResult::Events(HorizonEvents {
+                rise: HorizonEvent { time: dawn, .. },
+                set: HorizonEvent { time: dusk, .. },
+                ..
+            }),
+        ..
+    } = calculate_twilight_events(&TestSkyObject, &time, &location, &horizon)
+    else {
+        panic!("expected civil twilight events");
+    };
+
+    assert_eq!(dawn.hour(), 5);
+    assert_eq!(dawn.minute(), 44);
+
+    assert_eq!(dusk.hour(), 18);
+    assert_eq!(dusk.minute(), 15);
+}
+
+#[test]
+fn golden_hour_flat() {
+    let altitudes = [0.; HORIZON_SAMPLES];
+    let horizon = Horizon::new(altitudes);
+
+    let time = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2006, 8, 6).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+
+    let location = Location {
+        lat: 48.1,
+        lon: 11.6,
+        ..Default::default()
+    };
+
+    let LightWindows {
+        golden_hour:
+            HorizonWindows {
+                morning: Some(morning),
+                evening: Some(evening),
+                ..
+            },
+        ..
+    } = calculate_light_windows(&TestSkyObject, &time, &location, &horizon)
+    else {
+        panic!("expected golden hour windows");
+    };
+
+    assert_eq!(morning.start.hour(), 6);
+    assert_eq!(morning.start.minute(), 0);
+    assert_eq!(morning.end.hour(), 6);
+    assert_eq!(morning.end.minute(), 15);
+
+    assert_eq!(evening.start.hour(), 17);
+    assert_eq!(evening.start.minute(), 44);
+    assert_eq!(evening.end.hour(), 18);
+    assert_eq!(evening.end.minute(), 0);
+
+    assert_eq!(morning.duration().num_minutes(), 15);
+}
+
+#[test]
+fn rise_below_sea_horizon() {
+    // Terrain far below the observer, e.g. a deep valley
+    let altitudes = [-10f64.to_radians(); HORIZON_SAMPLES];
+    let horizon = Horizon::new(altitudes);
+
+    let time = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2006, 8, 6).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+
+    let location = Location {
+        lat: 48.1,
+        lon: 11.6,
+        elevation: 3000.,
+        ..Default::default()
+    };
+
+    let HorizonEvents {
+        rise: HorizonEvent { time: rise, .. },
+        ..
+    } = sky_service::calculate_rise_and_set(&TestSkyObject, &time, &location, &horizon).unwrap();
+
+    // The object rises at the sea-level horizon, 1.6° below the astronomical horizon
+    let expected = NaiveTime::from_hms_opt(5, 55, 54).unwrap();
+    assert!((rise.time() - expected).num_seconds().abs() < 10);
+}
+
+#[test]
+fn limb_contacts_flat() {
+    let altitudes = [0.; HORIZON_SAMPLES];
+    let horizon = Horizon::new(altitudes);
+
+    let time = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2006, 8, 6).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+
+    let location = Location {
+        lat: 48.1,
+        lon: 11.6,
+        ..Default::default()
+    };
+
+    let events = |contact: Contact| {
+        let HorizonEvents {
+            rise: HorizonEvent { time: rise, .. },
+            set: HorizonEvent { time: set, .. },
+            ..
+        } = sky_service::calculate_rise_and_set(
+            &Limb::new(&TestSkyObject, contact),
+            &time,
+            &location,
+            &horizon,
+        )
+        .unwrap();
+        (rise.time(), set.time())
+    };
+    let assert_close = |is: NaiveTime, want: (u32, u32, u32)| {
+        let want = NaiveTime::from_hms_opt(want.0, want.1, want.2).unwrap();
+        assert!(
+            (is - want).num_seconds().abs() < 10,
+            "{is} (is), {want} (want)"
+        );
+    };
+
+    // A radius of 1° takes about two and a half minutes to cross the horizon
+    let (rise, set) = events(Contact::UpperLimb);
+    assert_close(rise, (5, 57, 27));
+    assert_close(set, (18, 2, 33));
+
+    let (rise, set) = events(Contact::LowerLimb);
+    assert_close(rise, (6, 2, 33));
+    assert_close(set, (17, 57, 27));
+}
+
+#[test]
+fn visibility_intervals_ragged() {
+    // A wall of 40° between the azimuths 100° and 110°
+    let mut altitudes = [0.; HORIZON_SAMPLES];
+    altitudes[285..=312].fill(40f64.to_radians());
+    let horizon = Horizon::new(altitudes);
+
+    let start = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2006, 8, 6).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+    let end = start + Duration::try_days(1).unwrap();
+
+    let location = Location {
+        lat: 48.1,
+        lon: 11.6,
+        ..Default::default()
+    };
+
+    let intervals =
+        calculate_visibility_intervals(&TestSkyObject, &start, &end, &location, &horizon);
+    let times: Vec<_> = intervals
+        .iter()
+        .map(|interval| (interval.start.time(), interval.end.time()))
+        .collect();
+
+    assert_eq!(times.len(), 2, "{times:?}");
+    assert!(intervals
+        .iter()
+        .all(|i| i.rise.is_some() && i.set.is_some()));
+
+    let assert_minutes = |is: NaiveTime, hour: u32, minutes: std::ops::RangeInclusive<u32>| {
+        assert_eq!(is.hour(), hour, "{is}");
+        assert!(minutes.contains(&is.minute()), "{is}");
+    };
+    assert_minutes(times[0].0, 6, 0..=0);
+    assert_minutes(times[0].1, 6, 39..=40);
+    assert_minutes(times[1].0, 7, 18..=19);
+    assert_minutes(times[1].1, 18, 0..=0);
+
+    // Starting at noon, the object is already up
+    let start = start + Duration::try_hours(12).unwrap();
+    let end = start + Duration::try_days(1).unwrap();
+    let intervals =
+        calculate_visibility_intervals(&TestSkyObject, &start, &end, &location, &horizon);
+
+    assert_eq!(intervals.len(), 3);
+    assert!(intervals[0].rise.is_none());
+    assert_eq!(intervals[0].start, start.and_utc());
+    assert!(intervals[2].set.is_none());
+    assert_eq!(intervals[2].end, end.and_utc());
+}
+
+#[test]
+fn track_ragged() {
+    // A wall of 40° between the azimuths 100° and 110°
+    let mut altitudes = [0.; HORIZON_SAMPLES];
+    altitudes[285..=312].fill(40f64.to_radians());
+    let horizon = Horizon::new(altitudes);
+
+    let start = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2006, 8, 6).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+    let end = start + Duration::try_days(1).unwrap();
+
+    let location = Location {
+        lat: 48.1,
+        lon: 11.6,
+        ..Default::default()
+    };
+
+    let step = Duration::try_minutes(10).unwrap();
+    let track = calculate_track(&TestSkyObject, &start, &end, step, &location, &horizon);
+
+    // Only the samples from 6:00 to 18:00 are above the horizon
+    assert!((71..=73).contains(&track.len()), "{}", track.len());
+    assert!(track.iter().all(|point| point.altitude.0 > -1e-9));
+    assert!(track
+        .windows(2)
+        .all(|pair| pair[1].time - pair[0].time == step));
+
+    let at = |hour: u32, minute: u32| {
+        track
+            .iter()
+            .find(|point| point.time.hour() == hour && point.time.minute() == minute)
+            .unwrap()
+    };
+    assert!(at(6, 30).visible);
+    assert!(!at(7, 0).visible);
+    assert!(at(7, 30).visible);
+    assert!(at(12, 0).visible);
+}
+
+#[test]
+fn sunlight_in_a_narrow_valley() {
+    // Slopes rising 30° all around
+    let altitudes = [30f64.to_radians(); HORIZON_SAMPLES];
+    let horizon = Horizon::new(altitudes);
+
+    let start = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2006, 8, 6).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+    let end = start + Duration::try_days(1).unwrap();
+
+    let location = Location {
+        lat: 48.1,
+        lon: 11.6,
+        ..Default::default()
+    };
+
+    let sunlight = calculate_sunlight(&TestSkyObject, &start, &end, &location, &horizon);
+
+    // The object climbs above 30° at 7:17:53 and sinks below at 16:42:07
+    assert_eq!(sunlight.intervals.len(), 1);
+    let interval = &sunlight.intervals[0];
+    let assert_close = |is: NaiveTime, want: NaiveTime| {
+        assert!(
+            (is - want).num_seconds().abs() < 10,
+            "{is} (is), {want} (want)"
+        );
+    };
+    assert_close(
+        interval.start.time(),
+        NaiveTime::from_hms_opt(7, 17, 53).unwrap(),
+    );
+    assert_close(
+        interval.end.time(),
+        NaiveTime::from_hms_opt(16, 42, 7).unwrap(),
+    );
+    assert_eq!(sunlight.minutes, 564);
+
+    let three_pm = (start + Duration::try_hours(15).unwrap()).and_utc();
+    assert!(interval.start < three_pm && three_pm < interval.end);
+}
+
+#[test]
+fn duration_ragged() {
+    // The wall of `visibility_intervals_ragged` hides the object for about 40 minutes
+    let mut altitudes = [0.; HORIZON_SAMPLES];
+    altitudes[285..=312].fill(40f64.to_radians());
+    let horizon = Horizon::new(altitudes);
+
+    let start = NaiveDateTime::new(
+        NaiveDate::from_ymd_opt(2006, 8, 6).unwrap(),
+        NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+    );
+    let end = start + Duration::try_days(1).unwrap();
+
+    let location = Location {
+        lat: 48.1,
+        lon: 11.6,
+        ..Default::default()
+    };
+
+    let Ok(events) =
+        sky_service::calculate_rise_and_set(&TestSkyObject, &start, &location, &horizon)
+    else {
+        panic!("rise and set not found");
+    };
+    let intervals =
+        calculate_visibility_intervals(&TestSkyObject, &start, &end, &location, &horizon);
+    let visible: i64 = intervals
+        .iter()
+        .map(|interval| interval.duration().num_seconds())
+        .sum();
+
+    // The first set is behind the wall, but the object rises again after it
+    assert_eq!(events.set.time.hour(), 6);
+    assert!(
+        (events.duration - visible).abs() <= 1,
+        "{}",
+        events.duration
+    );
+    assert!((680 * 60..=682 * 60).contains(&events.duration));
+}
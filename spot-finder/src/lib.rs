@@ -38,6 +38,7 @@ async fn get_osm_data(loc: &Location, rad: u32) -> Result<String, anyhow::Error>
 pub struct Spot {
     pub kind: String,
     pub loc: Location,
+    /// Direction the spot faces (in degrees), measured from north towards east
     pub dir: Option<f64>,
 }
 
@@ -55,12 +56,10 @@ fn direction_of_node(node: &Node) -> Option<f64> {
         .find(|tag| tag.key == "direction")
         .map(|tag| tag.val.as_str())
         .map(direction::direction_from_string)
-        .map(|dir| {
-            if let Err(err) = &dir {
+        .inspect(|dir| {
+            if let Err(err) = dir {
                 println!("Couldn't parse direction of node {node:?}, {err}")
             }
-
-            dir
         })
         .and_then(Result::ok)
 }
@@ -5,6 +5,9 @@ use serde::{Deserialize, Serialize};
 pub struct Location {
     pub lat: f64,
     pub lon: f64,
+    /// Elevation above sea level in metres, if tagged
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub elevation: Option<f64>,
 }
 
 impl From<&Node> for Location {
@@ -12,6 +15,11 @@ impl From<&Node> for Location {
         Location {
             lat: value.lat,
             lon: value.lon,
+            elevation: value
+                .tags
+                .iter()
+                .find(|tag| tag.key == "ele")
+                .and_then(|tag| tag.val.trim_end_matches('m').trim().parse().ok()),
         }
     }
 }